@@ -1,28 +1,84 @@
+use std::fmt;
+
+/// A region of the source code given as byte offsets into the source.
+/// Both ends are inclusive i.e. `end` is the offset of the last byte in the region
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    pub fn empty() -> Self {
+        Self { start: 0, end: 0 }
+    }
+
+    /// The smallest span covering both `self` and `other`
+    pub fn to(self, other: Span) -> Self {
+        Self {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+}
+
 /// Contains information about the module that must be present / known for
 /// every module
 #[derive(Debug, PartialEq)]
-pub struct ModuleHeader {
-    /// module name
+pub struct ModuleHeader<'a> {
+    /// module name, with the segments of a nested module separated by `.`
     pub name: String,
 
-    // TODO: think about how to handle the imports and exports
-    /// imports of the module, Currently not implemented
-    pub imports: (),
+    /// The items made available to other modules
+    pub exposing: Exposing<'a>,
+
+    /// imports of the module
+    pub imports: Vec<Import<'a>>,
+}
+
+/// `import Foo.Bar as B exposing (x, Type(..))`
+#[derive(Debug, PartialEq)]
+pub struct Import<'a> {
+    pub path: Vec<Ident<'a>>,
+    /// Name the module is referred to by instead of its path
+    pub alias: Option<Ident<'a>>,
+    /// Items that can be used without qualifying them with the module name
+    pub exposing: Option<Exposing<'a>>,
+}
+
+/// The list following `exposing`
+#[derive(Debug, PartialEq)]
+pub enum Exposing<'a> {
+    /// `(..)`, everything defined in the module
+    All,
+    Items(Vec<Export<'a>>),
+}
+
+/// A single item of an exposing list
+#[derive(Debug, PartialEq)]
+pub enum Export<'a> {
+    /// A function or value e.g. `main`
+    Value(Ident<'a>),
+
+    /// A type e.g. `Color`, along with its variants if written as `Color(..)`
+    Type { ident: Ident<'a>, variants: bool },
 }
 
 /// The two types of modules in the language and information specific to each
 /// This has to be constructed for evaluation to take place.
-
 #[derive(Debug, PartialEq)]
 pub enum ModuleType<'a> {
     /// A module meant to be the main module or another entrypoint to an
     /// executable. It contains a definition that serves as the entrypoint /
     /// main fuction
-    ExecutableModule { entrypoint: Definition<'a> },
+    ExecutableModule { entrypoint: Ident<'a> },
 
-    // TODO: think about how to handle the imports and exports
-    /// An ordinary module containing a list of exports
-    LibraryModule { exports: Vec<()> },
+    /// An ordinary module, whose exports are listed in its header
+    LibraryModule,
 }
 
 /// A module in the meri language.
@@ -30,7 +86,7 @@ pub enum ModuleType<'a> {
 /// environment of the code and
 #[derive(Debug, PartialEq)]
 pub struct Module<'a> {
-    pub header: ModuleHeader,
+    pub header: ModuleHeader<'a>,
     pub typ: ModuleType<'a>,
     pub definitions: Vec<Definition<'a>>,
 }
@@ -39,24 +95,149 @@ pub struct Module<'a> {
 /// only items allowed in a module
 #[derive(Debug, PartialEq)]
 pub enum Definition<'a> {
-    TypeDefinition,
+    TypeDefinition(TypeDefinition<'a>),
     FunctionDefinition {
+        /// Text of the doc comments preceding the definition, one line per comment
+        doc: Option<String>,
         ident: Ident<'a>,
-        sig: FunctionSignature<'a>,
-        body: Expression,
+        /// The declared type, absent when the function is only given by clauses
+        sig: Option<FunctionSignature<'a>>,
+        /// The equations defining the function in source order. A body written
+        /// after the signature is the first clause, with the parameters of the
+        /// signature as its patterns
+        clauses: Vec<Clause<'a>>,
+    },
+
+    /// A definition that failed to parse, covering the tokens skipped to
+    /// recover from the error
+    Error {
+        span: Span,
     },
 }
 
+impl<'a> Definition<'a> {
+    /// Documentation attached to the definition
+    pub fn doc(&self) -> Option<&str> {
+        match self {
+            Definition::TypeDefinition(TypeDefinition { doc, .. }) => doc.as_deref(),
+            Definition::FunctionDefinition { doc, .. } => doc.as_deref(),
+            Definition::Error { .. } => None,
+        }
+    }
+}
+
+/// A new data type e.g.
+/// ```meri
+/// union Option<T> = {
+///   Some(T),
+///   None
+/// }
+/// ```
 #[derive(Debug, PartialEq)]
-pub struct FunctionSignature<'a> {
-    pub params: Vec<(Pattern<'a>, Option<TypePath<'a>>)>,
-    pub return_type: TypePath<'a>,
+pub struct TypeDefinition<'a> {
+    /// Text of the doc comments preceding the definition, one line per comment
+    pub doc: Option<String>,
+    pub kind: TypeDefinitionKind,
+    pub ident: Ident<'a>,
+    /// Names of the generic parameters, given either as `Option<T>` or `Option T`
+    pub params: Vec<Ident<'a>>,
+    pub body: TypeDefinitionBody<'a>,
+}
+
+/// The keyword a type definition was introduced with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeDefinitionKind {
+    Type,
+    Union,
+    Record,
+    Struct,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TypeDefinitionBody<'a> {
+    /// An enum with any number of variants `{ Red, Rgb(Int, Int, Int) }`
+    Variants(Vec<Variant<'a>>),
+
+    /// A record of named fields `{ size: Int, length: Int }`
+    Fields(Vec<Field<'a>>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Variant<'a> {
+    pub ident: Ident<'a>,
+    pub fields: VariantFields<'a>,
+}
+
+/// The data carried by a variant of an enum
+#[derive(Debug, PartialEq)]
+pub enum VariantFields<'a> {
+    /// `None`
+    Unit,
+
+    /// `Rgb(Int, Int, Int)` or, without parentheses, `NewRect Rect`
+    Tuple(Vec<TypeExpr<'a>>),
+
+    /// `Hsv { a: Int, b: Int, c: Int }`
+    Record(Vec<Field<'a>>),
 }
 
+/// A named field of a record
 #[derive(Debug, PartialEq)]
-pub struct TypePath<'a> {
+pub struct Field<'a> {
     pub ident: Ident<'a>,
+    pub typ: TypeExpr<'a>,
+}
+
+/// One equation of a function, matching its arguments against patterns
+/// ```meri
+/// fib(0) = 0
+/// fib(n) = fib(n - 1) + fib(n - 2)
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Clause<'a> {
+    pub params: Vec<Pattern<'a>>,
+    pub body: Expression<'a>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FunctionSignature<'a> {
+    /// Names of the generic parameters, `T` in `<T>(a: T) => T`
+    pub type_params: Vec<Ident<'a>>,
+    pub params: Vec<(Pattern<'a>, Option<TypeExpr<'a>>)>,
+    pub return_type: TypeExpr<'a>,
 }
+
+/// A type as written in the source
+#[derive(Debug, PartialEq)]
+pub enum TypeExpr<'a> {
+    /// A named type, qualified by the modules leading to it and applied to any
+    /// generic arguments e.g. `Int`, `Shapes.Rect`, `Option<Int>` or `IO(Unit)`
+    Named {
+        path: Vec<Ident<'a>>,
+        args: Vec<TypeExpr<'a>>,
+    },
+
+    /// A type variable, either a lowercase name `a` or a name bound by the
+    /// generic parameters of the enclosing definition
+    Variable(Ident<'a>),
+
+    /// `()`
+    Unit,
+
+    /// `(A, B, ...)` with at least two elements
+    Tuple(Vec<TypeExpr<'a>>),
+
+    /// `(A, B) => C`
+    Function {
+        params: Vec<TypeExpr<'a>>,
+        return_type: Box<TypeExpr<'a>>,
+    },
+
+    /// `{ name: Type, ... }`
+    Record(Vec<Field<'a>>),
+}
+
 /// A pattern used for matching against.
 /// All arguments of a function are patterns to allow destructuring of records and
 /// enums in function definitions
@@ -66,25 +247,349 @@ pub struct TypePath<'a> {
 ///        ...
 ///    }
 /// ```
-// TODO: flesh out this value
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Pattern<'a> {
+    /// `_`, matching anything without binding it
+    Wildcard,
+
     /// A simple binding of the a value to a variable name
     Binding(Ident<'a>),
 
-    /// A variant of an enum
-    DataVariant,
+    /// A value that must be equal to the literal
+    Literal(Literal),
 
-    /// Destructuring record fields
-    Record,
+    /// A variant of an enum, named by a capitalised or qualified path, along
+    /// with patterns for its fields e.g. `None`, `Some(x)`, `Color::Rgb(r, g, b)`.
+    /// A variant with named fields such as `Hsv { a, .. }` has a single
+    /// `Record` pattern as its field
+    DataVariant {
+        path: Vec<Ident<'a>>,
+        fields: Vec<Pattern<'a>>,
+    },
+
+    /// Destructuring record fields `{ r, g: green, .. }`. A field without a
+    /// pattern such as `r` binds the field to a variable of the same name
+    Record {
+        fields: Vec<(Ident<'a>, Pattern<'a>)>,
+        /// Whether the remaining fields are ignored with `..`
+        rest: bool,
+    },
+
+    /// `(a, b, ...)`. The unit value `()` is matched by an empty tuple
+    Tuple(Vec<Pattern<'a>>),
+
+    /// `a | b`, matching if any of the alternatives match
+    Or(Vec<Pattern<'a>>),
+
+    /// `pattern as ident`, binding the whole matched value as well
+    As {
+        pattern: Box<Pattern<'a>>,
+        ident: Ident<'a>,
+    },
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Ident<'a>(pub &'a str);
 
+/// A value written out directly in the source
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    /// An integer and its optional type suffix e.g. `10u8`
+    Int(i128, Option<NumberSuffix>),
+    /// A floating point number and its optional type suffix e.g. `3.0f32`
+    Float(f64, Option<NumberSuffix>),
+    String(String),
+    Char(char),
+    Bool(bool),
+}
+
+/// The type suffix of a numeric literal e.g. the `u8` in `255u8`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberSuffix {
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    F32,
+    F64,
+}
+
+impl NumberSuffix {
+    pub fn from_suffix(suffix: &str) -> Option<Self> {
+        use NumberSuffix::*;
+
+        match suffix {
+            "i8" => Some(I8),
+            "i16" => Some(I16),
+            "i32" => Some(I32),
+            "i64" => Some(I64),
+            "u8" => Some(U8),
+            "u16" => Some(U16),
+            "u32" => Some(U32),
+            "u64" => Some(U64),
+            "f32" => Some(F32),
+            "f64" => Some(F64),
+            _ => None,
+        }
+    }
+
+    pub fn is_float(self) -> bool {
+        matches!(self, NumberSuffix::F32 | NumberSuffix::F64)
+    }
+
+    /// The inclusive range of values representable by an integer type.
+    /// Float types have no integer range
+    pub fn int_range(self) -> Option<(i128, i128)> {
+        use NumberSuffix::*;
+
+        match self {
+            I8 => Some((i8::MIN.into(), i8::MAX.into())),
+            I16 => Some((i16::MIN.into(), i16::MAX.into())),
+            I32 => Some((i32::MIN.into(), i32::MAX.into())),
+            I64 => Some((i64::MIN.into(), i64::MAX.into())),
+            U8 => Some((0, u8::MAX.into())),
+            U16 => Some((0, u16::MAX.into())),
+            U32 => Some((0, u32::MAX.into())),
+            U64 => Some((0, u64::MAX.into())),
+            F32 | F64 => None,
+        }
+    }
+}
+
+impl fmt::Display for NumberSuffix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use NumberSuffix::*;
+
+        let suffix = match self {
+            I8 => "i8",
+            I16 => "i16",
+            I32 => "i32",
+            I64 => "i64",
+            U8 => "u8",
+            U16 => "u16",
+            U32 => "u32",
+            U64 => "u64",
+            F32 => "f32",
+            F64 => "f64",
+        };
+        write!(f, "{suffix}")
+    }
+}
+
 /// Type that may be evaluated to a simpler value
-// TODO: fill out
 #[derive(Debug, PartialEq)]
-pub enum Expression {
-    Unit,
+pub enum Expression<'a> {
+    /// The unit value `()`
+    Unit {
+        span: Span,
+    },
+
+    Literal {
+        value: Literal,
+        span: Span,
+    },
+
+    /// A named value, possibly qualified by the path leading to it e.g. `Option::Some`
+    Variable {
+        path: Vec<Ident<'a>>,
+        span: Span,
+    },
+
+    /// `(a, b, ...)` with at least two elements
+    Tuple {
+        elements: Vec<Expression<'a>>,
+        span: Span,
+    },
+
+    /// Application of a function to arguments, either juxtaposed as in
+    /// `math.log 2 3` or in parentheses as in `f(x, y)`
+    Call {
+        func: Box<Expression<'a>>,
+        args: Vec<Expression<'a>>,
+        span: Span,
+    },
+
+    /// Access of a field of a record or an item of a module e.g. `rect.size`
+    Field {
+        expr: Box<Expression<'a>>,
+        field: Ident<'a>,
+        span: Span,
+    },
+
+    Unary {
+        op: UnaryOp,
+        expr: Box<Expression<'a>>,
+        span: Span,
+    },
+
+    Binary {
+        op: BinaryOp,
+        lhs: Box<Expression<'a>>,
+        rhs: Box<Expression<'a>>,
+        span: Span,
+    },
+
+    /// `let x = value in body`, binding `x` only within `body`
+    Let {
+        binding: LetBinding<'a>,
+        body: Box<Expression<'a>>,
+        span: Span,
+    },
+
+    /// `if cond then then_branch else else_branch`
+    If {
+        cond: Box<Expression<'a>>,
+        then_branch: Box<Expression<'a>>,
+        else_branch: Box<Expression<'a>>,
+        span: Span,
+    },
+
+    /// `match scrutinee { pattern => body, ... }`, evaluating the body of the
+    /// first arm whose pattern matches
+    Match {
+        scrutinee: Box<Expression<'a>>,
+        arms: Vec<MatchArm<'a>>,
+        span: Span,
+    },
+
+    /// An anonymous function `\x, y => x + y`, with the same parameters as a
+    /// function signature
+    Lambda {
+        params: Vec<(Pattern<'a>, Option<TypeExpr<'a>>)>,
+        body: Box<Expression<'a>>,
+        span: Span,
+    },
+
+    /// A record value `{ size = 0, length = 1 }`
+    Record {
+        fields: Vec<(Ident<'a>, Expression<'a>)>,
+        span: Span,
+    },
+
+    /// `{ statement; ...; result }`. A `let` statement binds its names in the
+    /// statements following it and in the result
+    Block {
+        statements: Vec<Statement<'a>>,
+        result: Box<Expression<'a>>,
+        span: Span,
+    },
+
+    /// An expression that failed to parse, covering the tokens skipped to
+    /// recover from the error
+    Error {
+        span: Span,
+    },
+}
+
+impl<'a> Expression<'a> {
+    /// The region of the source the expression was parsed from
+    pub fn span(&self) -> Span {
+        match self {
+            Expression::Unit { span }
+            | Expression::Literal { span, .. }
+            | Expression::Variable { span, .. }
+            | Expression::Tuple { span, .. }
+            | Expression::Call { span, .. }
+            | Expression::Field { span, .. }
+            | Expression::Unary { span, .. }
+            | Expression::Binary { span, .. }
+            | Expression::Let { span, .. }
+            | Expression::If { span, .. }
+            | Expression::Match { span, .. }
+            | Expression::Lambda { span, .. }
+            | Expression::Record { span, .. }
+            | Expression::Block { span, .. }
+            | Expression::Error { span } => *span,
+        }
+    }
+}
+
+/// `let pattern: Type = value`
+#[derive(Debug, PartialEq)]
+pub struct LetBinding<'a> {
+    pub pattern: Pattern<'a>,
+    pub typ: Option<TypeExpr<'a>>,
+    pub value: Box<Expression<'a>>,
+    pub span: Span,
+}
+
+/// `pattern if guard => body`
+#[derive(Debug, PartialEq)]
+pub struct MatchArm<'a> {
+    pub pattern: Pattern<'a>,
+    /// Condition that must also hold for the arm to be taken
+    pub guard: Option<Expression<'a>>,
+    pub body: Expression<'a>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Statement<'a> {
+    Let(LetBinding<'a>),
+    /// An expression evaluated only for its effects
+    Expression(Expression<'a>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    /// `-`
+    Neg,
+    /// `!`
+    Not,
+}
+
+impl fmt::Display for UnaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnaryOp::Neg => write!(f, "-"),
+            UnaryOp::Not => write!(f, "!"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+    Eq,
+    NotEq,
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
+    And,
+    Or,
+    /// `|>`, passing the left hand side as the last argument of the right hand side
+    Pipe,
+}
+
+impl fmt::Display for BinaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Rem => "%",
+            BinaryOp::Pow => "^",
+            BinaryOp::Eq => "==",
+            BinaryOp::NotEq => "!=",
+            BinaryOp::Less => "<",
+            BinaryOp::LessEq => "<=",
+            BinaryOp::Greater => ">",
+            BinaryOp::GreaterEq => ">=",
+            BinaryOp::And => "&&",
+            BinaryOp::Or => "||",
+            BinaryOp::Pipe => "|>",
+        };
+        write!(f, "{op}")
+    }
 }
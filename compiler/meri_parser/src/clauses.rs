@@ -0,0 +1,218 @@
+//! Grouping the clauses of a function into a single definition.
+//!
+//! A function is given by an optional signature followed by its clauses, which
+//! must all follow each other and take the same number of parameters
+//! ```meri
+//! fib: (n: Int) => Int
+//! fib(0) = 0
+//! fib(1) = 1
+//! fib(n) = fib(n - 1) + fib(n - 2)
+//! ```
+
+use meri_ast::{Clause, Definition, FunctionSignature};
+
+use crate::{span::Span, ParseError, ParseErrorKind};
+
+/// Merges consecutive definitions of the same function, reporting clauses
+/// that are not contiguous or whose number of parameters does not match the
+/// rest of their function. Definitions in error are still kept
+pub(crate) fn group_clauses(
+    items: Vec<(Definition<'_>, Span)>,
+) -> (Vec<Definition<'_>>, Vec<ParseError>) {
+    let mut definitions = Vec::new();
+    let mut errors = Vec::new();
+
+    for (item, span) in items {
+        let Definition::FunctionDefinition {
+            doc,
+            ident,
+            sig,
+            clauses,
+        } = item
+        else {
+            definitions.push(item);
+            continue;
+        };
+
+        if let Some(Definition::FunctionDefinition {
+            doc: previous_doc,
+            ident: previous,
+            sig: previous_sig,
+            clauses: previous_clauses,
+        }) = definitions.last_mut()
+        {
+            if *previous == ident {
+                if sig.is_some() {
+                    errors.push(ParseError {
+                        kind: ParseErrorKind::MisplacedSignature,
+                        span,
+                        context: Vec::new(),
+                    });
+                }
+                if let Some(expected) = arity(previous_sig, previous_clauses) {
+                    check_arity(expected, &clauses, &mut errors);
+                }
+                if previous_doc.is_none() {
+                    *previous_doc = doc;
+                }
+                previous_clauses.extend(clauses);
+                continue;
+            }
+        }
+
+        let defined = definitions.iter().any(|definition| {
+            matches!(definition, Definition::FunctionDefinition { ident: other, .. } if *other == ident)
+        });
+        if defined {
+            errors.push(ParseError {
+                kind: ParseErrorKind::NonContiguousClauses,
+                span,
+                context: Vec::new(),
+            });
+        }
+        if let Some(expected) = arity(&sig, &clauses) {
+            check_arity(expected, &clauses, &mut errors);
+        }
+
+        definitions.push(Definition::FunctionDefinition {
+            doc,
+            ident,
+            sig,
+            clauses,
+        });
+    }
+
+    (definitions, errors)
+}
+
+/// The number of parameters of a function, given by its signature or else its first clause
+fn arity(sig: &Option<FunctionSignature<'_>>, clauses: &[Clause<'_>]) -> Option<usize> {
+    match sig {
+        Some(sig) => Some(sig.params.len()),
+        None => clauses.first().map(|clause| clause.params.len()),
+    }
+}
+
+fn check_arity(expected: usize, clauses: &[Clause<'_>], errors: &mut Vec<ParseError>) {
+    for clause in clauses {
+        if clause.params.len() != expected {
+            errors.push(ParseError {
+                kind: ParseErrorKind::ArityMismatch {
+                    expected,
+                    found: clause.params.len(),
+                },
+                span: clause.span,
+                context: Vec::new(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use meri_ast::{Definition, Ident};
+
+    use crate::{parse_definitions, significant_tokens, span::Span, ParseErrorKind};
+
+    use super::group_clauses;
+
+    fn group(input: &str) -> (Vec<Definition<'_>>, Vec<(ParseErrorKind, Span)>) {
+        let (tokens, _) = significant_tokens(input);
+        let (items, errors) = parse_definitions(&tokens);
+        assert_eq!(errors, []);
+        let (definitions, errors) = group_clauses(items);
+        let errors = errors.into_iter().map(|e| (e.kind, e.span)).collect();
+        (definitions, errors)
+    }
+
+    #[test]
+    fn groups_clauses() {
+        let (definitions, errors) = group(
+            "fib: (n: Int) => Int\n\
+             fib(0) = 0\n\
+             fib(1) = 1\n\
+             fib(n) = fib(n - 1) + fib(n - 2)\n\
+             one = 1",
+        );
+        assert!(errors.is_empty());
+        let [Definition::FunctionDefinition {
+            ident,
+            sig: Some(_),
+            clauses,
+            ..
+        }, Definition::FunctionDefinition {
+            sig: None,
+            clauses: one,
+            ..
+        }] = &definitions[..]
+        else {
+            panic!("expected two function definitions");
+        };
+        assert_eq!(*ident, Ident("fib"));
+        assert_eq!(clauses.len(), 3);
+        assert_eq!(one.len(), 1);
+    }
+
+    #[test]
+    fn signature_with_body_and_clause() {
+        let (definitions, errors) =
+            group("hello: String = { \"Hujambo\" }\nhello = { \"Shikamoo\" }");
+        assert!(errors.is_empty());
+        let [Definition::FunctionDefinition { clauses, .. }] = &definitions[..] else {
+            panic!("expected a single function definition");
+        };
+        assert_eq!(clauses.len(), 2);
+    }
+
+    #[test]
+    fn keeps_first_doc() {
+        let (definitions, _) = group("--- Factorial\nfact: n => Int\nfact(0) = 1\nfact(n) = n");
+        assert_eq!(definitions[0].doc(), Some("Factorial"));
+    }
+
+    #[test]
+    fn non_contiguous_clauses() {
+        let (definitions, errors) = group("f(0) = 0\ng = 1\nf(n) = n");
+        assert_eq!(definitions.len(), 3);
+        assert_eq!(
+            errors,
+            [(ParseErrorKind::NonContiguousClauses, Span::new(15, 22))]
+        );
+    }
+
+    #[test]
+    fn misplaced_signature() {
+        let (_, errors) = group("f(0) = 0\nf: n => Int\nf(n) = n");
+        assert_eq!(
+            errors,
+            [(ParseErrorKind::MisplacedSignature, Span::new(9, 19))]
+        );
+    }
+
+    #[test]
+    fn arity_mismatch() {
+        let (_, errors) = group("add: (a, b) => Int\nadd(0, b) = b\nadd(a) = a");
+        assert_eq!(
+            errors,
+            [(
+                ParseErrorKind::ArityMismatch {
+                    expected: 2,
+                    found: 1
+                },
+                Span::new(33, 42)
+            )]
+        );
+
+        let (_, errors) = group("f(0, 1) = 0\nf(n) = n");
+        assert_eq!(
+            errors,
+            [(
+                ParseErrorKind::ArityMismatch {
+                    expected: 2,
+                    found: 1
+                },
+                Span::new(12, 19)
+            )]
+        );
+    }
+}
@@ -0,0 +1,356 @@
+//! Data definitions introduced by `type`, `union`, `record` or `struct`.
+//!
+//! The body of a definition either lists the variants of an enum or, when its
+//! first entry is a `name: Type` pair, the fields of a record
+//! ```meri
+//! type Color = {
+//!   Rgb(Int, Int, Int),
+//!   Hsv {a: Int, b: Int, c: Int},
+//! }
+//!
+//! record Wow =
+//!   { first_name: String
+//!   , age: Int
+//!   }
+//! ```
+
+use meri_ast::{
+    Ident, TypeDefinition, TypeDefinitionBody, TypeDefinitionKind, Variant, VariantFields,
+};
+use nom::{multi::many0, sequence::delimited, Parser};
+use nom_supreme::ParserExt;
+
+use crate::{
+    comma_separated,
+    error::{record, Expected, TokenError},
+    ident, parse_doc_comments,
+    token::{Token, TokenType},
+    token_type,
+    types::{bind_type_params, parse_fields, parse_generic_params, parse_type},
+    ParseResult,
+};
+
+pub(crate) fn parse_type_definition<'a, 'b>(
+    input: &'a [Token<'b>],
+) -> ParseResult<'a, 'b, TypeDefinition<'b>> {
+    let (input, doc) = parse_doc_comments(input)?;
+    let (input, kind) = parse_kind(input)?;
+    let (input, ident) = ident(input)?;
+    let (input, params) = parse_type_params(input)?;
+    let (input, _) = token_type(TokenType::Equal)(input)?;
+    let (input, mut body) = (|input| parse_body(input, kind))
+        .context(format!("definition of type `{}`", ident.0))
+        .parse(input)?;
+    bind_body_params(&mut body, &params);
+
+    Ok((
+        input,
+        TypeDefinition {
+            doc,
+            kind,
+            ident,
+            params,
+            body,
+        },
+    ))
+}
+
+fn parse_kind<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, TypeDefinitionKind> {
+    let kind = match input.first().map(|t| &t.typ) {
+        Some(TokenType::Type) => TypeDefinitionKind::Type,
+        Some(TokenType::Union) => TypeDefinitionKind::Union,
+        Some(TokenType::Record) => TypeDefinitionKind::Record,
+        Some(TokenType::Struct) => TypeDefinitionKind::Struct,
+        _ => {
+            let keywords = [
+                TokenType::Type,
+                TokenType::Union,
+                TokenType::Record,
+                TokenType::Struct,
+            ];
+            return Err(nom::Err::Error(TokenError::expected(
+                input,
+                keywords.iter().map(Expected::token).collect(),
+            )));
+        }
+    };
+
+    Ok((&input[1..], kind))
+}
+
+/// Generic parameters, either in angle brackets `<a, b>` or juxtaposed `a b`
+fn parse_type_params<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Vec<Ident<'b>>> {
+    match parse_generic_params(input)? {
+        (input, params) if !params.is_empty() => Ok((input, params)),
+        _ => many0(ident)(input),
+    }
+}
+
+/// `{ ... }` containing either variants or fields. An empty body is an enum
+/// without variants unless the definition is a `record` or `struct`
+fn parse_body<'a, 'b>(
+    input: &'a [Token<'b>],
+    kind: TypeDefinitionKind,
+) -> ParseResult<'a, 'b, TypeDefinitionBody<'b>> {
+    let (rest, _) = token_type(TokenType::LBrace)(input)?;
+
+    let has_fields = match rest.first().map(|t| &t.typ) {
+        Some(TokenType::RBrace) => matches!(
+            kind,
+            TypeDefinitionKind::Record | TypeDefinitionKind::Struct
+        ),
+        _ => rest.get(1).map(|t| &t.typ) == Some(&TokenType::Colon),
+    };
+
+    if has_fields {
+        let (rest, fields) = parse_fields(input)?;
+        Ok((rest, TypeDefinitionBody::Fields(fields)))
+    } else {
+        let (rest, variants) = delimited(
+            token_type(TokenType::LBrace),
+            comma_separated(parse_variant),
+            token_type(TokenType::RBrace),
+        )(input)?;
+        Ok((rest, TypeDefinitionBody::Variants(variants)))
+    }
+}
+
+/// Turns the uses of the generic parameters in the fields of `body` into type variables
+fn bind_body_params<'a>(body: &mut TypeDefinitionBody<'a>, params: &[Ident<'a>]) {
+    let types = match body {
+        TypeDefinitionBody::Fields(fields) => fields.iter_mut().map(|f| &mut f.typ).collect(),
+        TypeDefinitionBody::Variants(variants) => variants
+            .iter_mut()
+            .flat_map(|variant| match &mut variant.fields {
+                VariantFields::Unit => Vec::new(),
+                VariantFields::Tuple(types) => types.iter_mut().collect(),
+                VariantFields::Record(fields) => fields.iter_mut().map(|f| &mut f.typ).collect(),
+            })
+            .collect::<Vec<_>>(),
+    };
+
+    for typ in types {
+        bind_type_params(typ, params);
+    }
+}
+
+/// `Name`, `Name(Type, ...)`, `Name { field: Type, ... }` or `Name Type ...`
+fn parse_variant<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Variant<'b>> {
+    let (input, ident) = ident(input)?;
+
+    let (input, fields) = match input.first().map(|t| &t.typ) {
+        Some(TokenType::Lparen) => {
+            let (input, types) = delimited(
+                token_type(TokenType::Lparen),
+                comma_separated(parse_type),
+                token_type(TokenType::RParen),
+            )(input)?;
+            (input, VariantFields::Tuple(types))
+        }
+        Some(TokenType::LBrace) => {
+            let (input, fields) = parse_fields(input)?;
+            (input, VariantFields::Record(fields))
+        }
+        _ => {
+            record(input, || {
+                Expected::tokens(&[TokenType::Lparen, TokenType::LBrace])
+            });
+            let (input, types) = many0(parse_type)(input)?;
+            let fields = if types.is_empty() {
+                VariantFields::Unit
+            } else {
+                VariantFields::Tuple(types)
+            };
+            (input, fields)
+        }
+    };
+
+    Ok((input, Variant { ident, fields }))
+}
+
+#[cfg(test)]
+mod tests {
+    use meri_ast::{
+        Definition, Field, Ident, TypeDefinition, TypeDefinitionBody, TypeDefinitionKind, TypeExpr,
+        Variant, VariantFields,
+    };
+
+    use crate::{parse_definitions, significant_tokens, token::TokenType};
+
+    use super::parse_type_definition;
+
+    fn parse(input: &str) -> TypeDefinition<'_> {
+        let (tokens, _) = significant_tokens(input);
+        let (rest, definition) = parse_type_definition(&tokens).unwrap();
+        assert_eq!(rest[0].typ, TokenType::EOF, "unparsed input in {input:?}");
+        definition
+    }
+
+    fn named(ident: &str) -> TypeExpr<'_> {
+        TypeExpr::Named {
+            path: vec![Ident(ident)],
+            args: vec![],
+        }
+    }
+
+    fn variant<'a>(ident: &'a str, fields: VariantFields<'a>) -> Variant<'a> {
+        Variant {
+            ident: Ident(ident),
+            fields,
+        }
+    }
+
+    fn field<'a>(ident: &'a str, typ: &'a str) -> Field<'a> {
+        Field {
+            ident: Ident(ident),
+            typ: named(typ),
+        }
+    }
+
+    #[test]
+    fn enum_without_variants() {
+        assert_eq!(
+            parse("type Void = {}"),
+            TypeDefinition {
+                doc: None,
+                kind: TypeDefinitionKind::Type,
+                ident: Ident("Void"),
+                params: vec![],
+                body: TypeDefinitionBody::Variants(vec![])
+            }
+        );
+    }
+
+    #[test]
+    fn enum_variants() {
+        assert_eq!(
+            parse("type Unit = { Unit }").body,
+            TypeDefinitionBody::Variants(vec![variant("Unit", VariantFields::Unit)])
+        );
+        assert_eq!(
+            parse("type Weekdays = { Monday, Tuesday, }").body,
+            TypeDefinitionBody::Variants(vec![
+                variant("Monday", VariantFields::Unit),
+                variant("Tuesday", VariantFields::Unit)
+            ])
+        );
+    }
+
+    #[test]
+    fn variants_with_data() {
+        assert_eq!(
+            parse("type Color = { Rgb(Int, Int, Int), Hsv {a: Int, b: Int}, }").body,
+            TypeDefinitionBody::Variants(vec![
+                variant(
+                    "Rgb",
+                    VariantFields::Tuple(vec![named("Int"), named("Int"), named("Int")])
+                ),
+                variant(
+                    "Hsv",
+                    VariantFields::Record(vec![field("a", "Int"), field("b", "Int")])
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn generic_params() {
+        let definition = parse("union Either<a, b> = { Left(a), Right(b) }");
+        assert_eq!(definition.kind, TypeDefinitionKind::Union);
+        assert_eq!(definition.params, [Ident("a"), Ident("b")]);
+
+        let definition = parse("type NewRect a = { NewRect Rect }");
+        assert_eq!(definition.params, [Ident("a")]);
+        assert_eq!(
+            definition.body,
+            TypeDefinitionBody::Variants(vec![variant(
+                "NewRect",
+                VariantFields::Tuple(vec![named("Rect")])
+            )])
+        );
+    }
+
+    #[test]
+    fn records() {
+        let definition = parse("record Wow =\n  { first_name: String\n  , age: Int\n  }");
+        assert_eq!(definition.kind, TypeDefinitionKind::Record);
+        assert_eq!(
+            definition.body,
+            TypeDefinitionBody::Fields(vec![field("first_name", "String"), field("age", "Int")])
+        );
+
+        assert_eq!(
+            parse("struct Rect<a> = { size: Int, length: a, }").body,
+            TypeDefinitionBody::Fields(vec![
+                field("size", "Int"),
+                Field {
+                    ident: Ident("length"),
+                    typ: TypeExpr::Variable(Ident("a"))
+                }
+            ])
+        );
+        assert_eq!(
+            parse("struct Empty = {}").body,
+            TypeDefinitionBody::Fields(vec![])
+        );
+    }
+
+    #[test]
+    fn binds_generic_params() {
+        assert_eq!(
+            parse("union Option<T> = { Some(T), None }").body,
+            TypeDefinitionBody::Variants(vec![
+                variant(
+                    "Some",
+                    VariantFields::Tuple(vec![TypeExpr::Variable(Ident("T"))])
+                ),
+                variant("None", VariantFields::Unit)
+            ])
+        );
+    }
+
+    #[test]
+    fn doc_comments() {
+        let definition = parse("--- Nothing at all\ntype Void = {}");
+        assert_eq!(definition.doc.as_deref(), Some("Nothing at all"));
+    }
+
+    #[test]
+    fn rejects_mixed_body() {
+        let (tokens, _) = significant_tokens("type Mixed = { a: Int, B }");
+        assert!(parse_type_definition(&tokens).is_err());
+    }
+
+    #[test]
+    fn data_definitions_example() {
+        let source = include_str!("../../../examples/data_definitions.meri");
+        let (tokens, _) = significant_tokens(source);
+        let (definitions, errors) = parse_definitions(&tokens);
+        assert_eq!(errors, []);
+
+        let names = definitions
+            .iter()
+            .map(|(definition, _)| match definition {
+                Definition::TypeDefinition(TypeDefinition { ident, .. })
+                | Definition::FunctionDefinition { ident, .. } => ident.0,
+                Definition::Error { .. } => panic!("unexpected syntax error"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "Void",
+                "Unit",
+                "Weekdays",
+                "Color",
+                "Option",
+                "Either",
+                "Wow",
+                "Rect",
+                "NewRect",
+                "maybe_int",
+                "some_rect"
+            ]
+        );
+    }
+}
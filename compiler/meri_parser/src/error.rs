@@ -0,0 +1,290 @@
+//! Errors reported by the parser.
+//!
+//! The token parsers fail with a [`TokenError`], which keeps what was expected
+//! at the furthest token reached along with what was being parsed there. When
+//! parsing gives up, it becomes a [`ParseError`] describing the token found.
+//!
+//! Optional parts of the grammar, such as the `: Type` of a parameter, are
+//! skipped without failing, so what they expected is recorded as the token
+//! parsers fail and added to the error at the same token. For
+//! `id : (x Number) => Number`, where `x` may be followed by its type, the next
+//! parameter or the end of the parameters, that is
+//! ```text
+//! expected one of `(`, `)`, `,`, `.`, `:`, `::`, `<`, `as`, `|`, found `Number`
+//!   while parsing function signature of `id`
+//! ```
+
+use std::{cell::RefCell, fmt::Display};
+
+use nom_supreme::context::ContextError;
+
+use crate::{
+    lexer::LexErrorKind,
+    span::Span,
+    token::{Token, TokenType},
+    ParseResult,
+};
+
+/// An error in the structure of the source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    /// The span of the token at which parsing failed or of the offending definition
+    pub span: Span,
+    /// What was being parsed when the error occurred, outermost first
+    pub context: Vec<String>,
+}
+
+/// The different kinds of errors that can occur during parsing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// A token that does not fit the grammar, along with what would have been
+    /// accepted in its place
+    Unexpected {
+        found: String,
+        expected: Vec<Expected>,
+    },
+    /// A clause of a function separated from its earlier clauses by other definitions
+    NonContiguousClauses,
+    /// A signature following the signature or clauses of the same function
+    MisplacedSignature,
+    /// A clause with a different number of parameters than the rest of its function
+    ArityMismatch { expected: usize, found: usize },
+    /// A `Main` module without a `main` function to run
+    MissingEntrypoint,
+    /// Text that does not form a valid token, or an integer literal that does
+    /// not fit its type
+    Lex(LexErrorKind),
+}
+
+/// Something the parser would have accepted in place of an unexpected token
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Expected {
+    /// A particular token, as written in the source
+    Token(String),
+    Identifier,
+    Literal,
+    EndOfInput,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.kind)?;
+        for context in self.context.iter().rev() {
+            write!(f, "\n  while parsing {context}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for ParseErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use ParseErrorKind::*;
+
+        match self {
+            Unexpected { found, expected } => match &expected[..] {
+                [] => write!(f, "unexpected {found}"),
+                [expected] => write!(f, "expected {expected}, found {found}"),
+                _ => {
+                    let expected = expected
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    write!(f, "expected one of {expected}, found {found}")
+                }
+            },
+            NonContiguousClauses => {
+                write!(
+                    f,
+                    "clauses of a function must not be separated by other definitions"
+                )
+            }
+            MisplacedSignature => {
+                write!(
+                    f,
+                    "a signature must come once, before the clauses of its function"
+                )
+            }
+            ArityMismatch { expected, found } => {
+                write!(
+                    f,
+                    "expected {expected} parameters but this clause has {found}"
+                )
+            }
+            MissingEntrypoint => write!(f, "a `Main` module must define a `main` function"),
+            Lex(kind) => write!(f, "{kind}"),
+        }
+    }
+}
+
+impl Display for Expected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expected::Token(token) => write!(f, "`{token}`"),
+            Expected::Identifier => write!(f, "identifier"),
+            Expected::Literal => write!(f, "literal"),
+            Expected::EndOfInput => write!(f, "end of input"),
+        }
+    }
+}
+
+impl Expected {
+    pub(crate) fn token(typ: &TokenType<'_>) -> Self {
+        match typ {
+            TokenType::EOF => Expected::EndOfInput,
+            typ => Expected::Token(typ.to_string()),
+        }
+    }
+
+    pub(crate) fn tokens(types: &[TokenType<'_>]) -> Vec<Self> {
+        types.iter().map(Expected::token).collect()
+    }
+}
+
+/// The error of the token parsers
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TokenError<'a, 'b> {
+    /// The input starting at the token that could not be parsed
+    pub(crate) input: &'a [Token<'b>],
+    /// Sorted and without duplicates
+    pub(crate) expected: Vec<Expected>,
+    /// What was being parsed, innermost first as contexts are added while
+    /// the parsers return
+    pub(crate) context: Vec<String>,
+}
+
+impl<'a, 'b> TokenError<'a, 'b> {
+    pub(crate) fn expected(input: &'a [Token<'b>], expected: Vec<Expected>) -> Self {
+        record(input, || expected.clone());
+        let mut error = TokenError {
+            input,
+            expected,
+            context: Vec::new(),
+        };
+        error.expected.sort();
+        error.expected.dedup();
+        error
+    }
+
+    /// Adds the description of what was being parsed, from the inside out
+    pub(crate) fn within(mut self, context: impl Into<String>) -> Self {
+        self.context.push(context.into());
+        self
+    }
+
+    /// Adds what the token parsers recorded as expected at the token where
+    /// this error occurred
+    pub(crate) fn with_recorded(mut self, recorded: &Expectations) -> Self {
+        let at = self.input.len();
+        self.expected.extend(
+            recorded
+                .0
+                .iter()
+                .filter(|(remaining, _)| *remaining == at)
+                .map(|(_, expected)| expected.clone()),
+        );
+        self.expected.sort();
+        self.expected.dedup();
+        self
+    }
+
+    pub(crate) fn into_parse_error(self) -> ParseError {
+        let (found, span) = match self.input.first() {
+            Some(token) => (describe(token), token.span),
+            None => ("end of input".to_string(), Span::empty()),
+        };
+
+        ParseError {
+            kind: ParseErrorKind::Unexpected {
+                found,
+                expected: self.expected,
+            },
+            span,
+            context: self.context.into_iter().rev().collect(),
+        }
+    }
+}
+
+/// What the token parsers expected where they failed, by the number of
+/// tokens left from there. Every input parsed while recording is the end of
+/// the same tokens, so this identifies the token
+#[derive(Debug, Default)]
+pub(crate) struct Expectations(Vec<(usize, Expected)>);
+
+thread_local! {
+    static RECORDING: RefCell<Option<Expectations>> = const { RefCell::new(None) };
+}
+
+/// Runs `parse`, recording what the token parsers expect wherever they fail
+pub(crate) fn recording_expected<R>(parse: impl FnOnce() -> R) -> (R, Expectations) {
+    let outer = RECORDING.replace(Some(Expectations::default()));
+    let result = parse();
+    let recorded = RECORDING.replace(outer).unwrap_or_default();
+
+    (result, recorded)
+}
+
+/// Notes that `expected` would have been accepted at the start of `input`,
+/// when recording
+pub(crate) fn record(input: &[Token<'_>], expected: impl FnOnce() -> Vec<Expected>) {
+    RECORDING.with_borrow_mut(|recording| {
+        if let Some(Expectations(recorded)) = recording {
+            recorded.extend(
+                expected()
+                    .into_iter()
+                    .map(|expected| (input.len(), expected)),
+            );
+        }
+    });
+}
+
+/// How a token is referred to in an error
+fn describe(token: &Token<'_>) -> String {
+    match token.typ {
+        TokenType::EOF => "end of input".to_string(),
+        // marks the start of the next definition
+        TokenType::Newline => "end of definition".to_string(),
+        ref typ => format!("`{typ}`"),
+    }
+}
+
+impl<'a, 'b> nom::error::ParseError<&'a [Token<'b>]> for TokenError<'a, 'b> {
+    fn from_error_kind(input: &'a [Token<'b>], _kind: nom::error::ErrorKind) -> Self {
+        TokenError::expected(input, Vec::new())
+    }
+
+    fn append(_input: &'a [Token<'b>], _kind: nom::error::ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    /// Keeps the alternative that got furthest, merging what was expected
+    /// when both failed at the same token
+    fn or(mut self, other: Self) -> Self {
+        match self.input.len().cmp(&other.input.len()) {
+            std::cmp::Ordering::Less => self,
+            std::cmp::Ordering::Greater => other,
+            std::cmp::Ordering::Equal => {
+                self.expected.extend(other.expected);
+                self.expected.sort();
+                self.expected.dedup();
+                if other.context.len() > self.context.len() {
+                    self.context = other.context;
+                }
+                self
+            }
+        }
+    }
+}
+
+impl<'a, 'b> ContextError<&'a [Token<'b>], &'static str> for TokenError<'a, 'b> {
+    fn add_context(_location: &'a [Token<'b>], ctx: &'static str, other: Self) -> Self {
+        other.within(ctx)
+    }
+}
+
+impl<'a, 'b> ContextError<&'a [Token<'b>], String> for TokenError<'a, 'b> {
+    fn add_context(_location: &'a [Token<'b>], ctx: String, other: Self) -> Self {
+        other.within(ctx)
+    }
+}
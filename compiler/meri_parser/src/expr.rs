@@ -0,0 +1,1077 @@
+//! Expressions.
+//!
+//! Binary operators are parsed by precedence climbing. From loosest to tightest
+//! binding they are
+//! ```text
+//! |>
+//! ||
+//! &&
+//! ==  !=  <  <=  >  >=
+//! +  -
+//! *  /  %
+//! ^                       (right associative)
+//! ```
+//! followed by the prefix operators `-` and `!`, which bind looser than `^` so
+//! that `-x ^ 2` is `-(x ^ 2)`, and then function application and field access.
+//! A number negated on its own becomes a negative literal, while `-2 ^ 2` is
+//! `-(2 ^ 2)` like any other operand.
+//!
+//! `let ... in`, `if ... then ... else` and lambdas `\x => ...` extend as far to
+//! the right as possible. Inside blocks,
+//! statements are separated by `;` and the block ends with its result
+//! ```meri
+//! {
+//!   let x: Int = 2;
+//!   print x;
+//!   x ^ 2
+//! }
+//! ```
+
+use meri_ast::{BinaryOp, Expression, LetBinding, MatchArm, Statement, UnaryOp};
+use nom::{
+    branch::alt,
+    combinator::opt,
+    multi::separated_list1,
+    sequence::{delimited, preceded, terminated},
+    Parser,
+};
+use nom_supreme::ParserExt;
+
+use crate::{
+    comma_list,
+    error::{record, Expected, TokenError},
+    ident, list_end, literal, negated, parse_function_params, parse_single_parameter,
+    pattern::parse_pattern,
+    recovery::{error_expression, skip_statement},
+    span::Span,
+    token::{Token, TokenType},
+    token_type, trailing_comma_list,
+    types::parse_type,
+    ParseResult,
+};
+
+/// Operands of prefix operators may only contain operators binding tighter than this
+const UNARY_PRECEDENCE: u8 = 7;
+
+/// The tokens other than identifiers and literals an operand may start with
+const OPERAND_STARTS: [TokenType<'static>; 8] = [
+    TokenType::Lparen,
+    TokenType::LBrace,
+    TokenType::Minus,
+    TokenType::Exclam,
+    TokenType::Let,
+    TokenType::If,
+    TokenType::Match,
+    TokenType::BackSlash,
+];
+
+const BINARY_OPERATORS: [TokenType<'static>; 15] = [
+    TokenType::Plus,
+    TokenType::Minus,
+    TokenType::Star,
+    TokenType::Slash,
+    TokenType::Percent,
+    TokenType::Caret,
+    TokenType::DoubleEqual,
+    TokenType::NotEqual,
+    TokenType::LAngleBracket,
+    TokenType::LessEqual,
+    TokenType::RAngleBracket,
+    TokenType::GreaterEqual,
+    TokenType::DoubleAmper,
+    TokenType::DoubleVbar,
+    TokenType::Pipe,
+];
+
+pub(crate) fn parse_expr<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Expression<'b>> {
+    parse_binary(input, 0)
+}
+
+/// Precedence of a binary operator and whether it is right associative
+fn precedence(op: BinaryOp) -> (u8, bool) {
+    match op {
+        BinaryOp::Pipe => (1, false),
+        BinaryOp::Or => (2, false),
+        BinaryOp::And => (3, false),
+        BinaryOp::Eq
+        | BinaryOp::NotEq
+        | BinaryOp::Less
+        | BinaryOp::LessEq
+        | BinaryOp::Greater
+        | BinaryOp::GreaterEq => (4, false),
+        BinaryOp::Add | BinaryOp::Sub => (5, false),
+        BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem => (6, false),
+        BinaryOp::Pow => (7, true),
+    }
+}
+
+fn binary_op(typ: &TokenType<'_>) -> Option<BinaryOp> {
+    let op = match typ {
+        TokenType::Plus => BinaryOp::Add,
+        TokenType::Minus => BinaryOp::Sub,
+        TokenType::Star => BinaryOp::Mul,
+        TokenType::Slash => BinaryOp::Div,
+        TokenType::Percent => BinaryOp::Rem,
+        TokenType::Caret => BinaryOp::Pow,
+        TokenType::DoubleEqual => BinaryOp::Eq,
+        TokenType::NotEqual => BinaryOp::NotEq,
+        TokenType::LAngleBracket => BinaryOp::Less,
+        TokenType::LessEqual => BinaryOp::LessEq,
+        TokenType::RAngleBracket => BinaryOp::Greater,
+        TokenType::GreaterEqual => BinaryOp::GreaterEq,
+        TokenType::DoubleAmper => BinaryOp::And,
+        TokenType::DoubleVbar => BinaryOp::Or,
+        TokenType::Pipe => BinaryOp::Pipe,
+        _ => return None,
+    };
+
+    Some(op)
+}
+
+fn parse_binary<'a, 'b>(
+    mut input: &'a [Token<'b>],
+    min_precedence: u8,
+) -> ParseResult<'a, 'b, Expression<'b>> {
+    let (rest, mut lhs) = parse_unary(input)?;
+    input = rest;
+
+    while let Some(op) = input.first().and_then(|token| binary_op(&token.typ)) {
+        let (precedence, right_assoc) = precedence(op);
+        if precedence < min_precedence {
+            break;
+        }
+        let next_min = if right_assoc {
+            precedence
+        } else {
+            precedence + 1
+        };
+
+        let (rest, rhs) = parse_binary(&input[1..], next_min)?;
+        let span = lhs.span().to(rhs.span());
+        lhs = Expression::Binary {
+            op,
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+            span,
+        };
+        input = rest;
+    }
+    record(input, || Expected::tokens(&BINARY_OPERATORS));
+
+    Ok((input, lhs))
+}
+
+fn parse_unary<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Expression<'b>> {
+    let op = match input.first().map(|t| &t.typ) {
+        Some(TokenType::Minus) => UnaryOp::Neg,
+        Some(TokenType::Exclam) => UnaryOp::Not,
+        Some(TokenType::Let) => return parse_let.context("let expression").parse(input),
+        Some(TokenType::If) => return parse_if.context("if expression").parse(input),
+        Some(TokenType::Match) => return parse_match.context("match expression").parse(input),
+        Some(TokenType::BackSlash) => return parse_lambda.context("lambda").parse(input),
+        _ => return parse_application(input),
+    };
+
+    let (rest, expr) = parse_binary(&input[1..], UNARY_PRECEDENCE)?;
+    let span = input[0].span.to(expr.span());
+
+    // a negated number is a negative literal, unless the operand is more than
+    // the number as in `-2 ^ 2`
+    if let (UnaryOp::Neg, Expression::Literal { value, .. }) = (op, &expr) {
+        if let Some(value) = negated(value) {
+            return Ok((rest, Expression::Literal { value, span }));
+        }
+    }
+
+    Ok((
+        rest,
+        Expression::Unary {
+            op,
+            expr: Box::new(expr),
+            span,
+        },
+    ))
+}
+
+/// `let binding in body`
+fn parse_let<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Expression<'b>> {
+    let (rest, binding) = parse_let_binding(input)?;
+    let (rest, _) = token_type(TokenType::In)(rest)?;
+    let (rest, body) = parse_expr(rest)?;
+    let span = binding.span.to(body.span());
+
+    Ok((
+        rest,
+        Expression::Let {
+            binding,
+            body: Box::new(body),
+            span,
+        },
+    ))
+}
+
+/// `if cond then then_branch else else_branch`
+fn parse_if<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Expression<'b>> {
+    let (rest, cond) = preceded(token_type(TokenType::If), parse_expr)(input)?;
+    let (rest, then_branch) = preceded(token_type(TokenType::Then), parse_expr)(rest)?;
+    let (rest, else_branch) = preceded(token_type(TokenType::Else), parse_expr)(rest)?;
+
+    Ok((
+        rest,
+        Expression::If {
+            cond: Box::new(cond),
+            then_branch: Box::new(then_branch),
+            else_branch: Box::new(else_branch),
+            span: consumed_span(input, rest),
+        },
+    ))
+}
+
+/// `match scrutinee { arm, ... }` allowing a trailing comma after the last arm
+fn parse_match<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Expression<'b>> {
+    let (rest, scrutinee) = preceded(token_type(TokenType::Match), parse_expr)(input)?;
+    let (rest, arms) = preceded(
+        token_type(TokenType::LBrace),
+        trailing_comma_list(parse_match_arm.context("match arm"), TokenType::RBrace),
+    )(rest)?;
+
+    Ok((
+        rest,
+        Expression::Match {
+            scrutinee: Box::new(scrutinee),
+            arms,
+            span: consumed_span(input, rest),
+        },
+    ))
+}
+
+/// `pattern if guard => body`
+fn parse_match_arm<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, MatchArm<'b>> {
+    let (rest, pattern) = parse_pattern(input)?;
+    let (rest, guard) = opt(preceded(token_type(TokenType::If), parse_expr))(rest)?;
+    let (rest, body) = preceded(token_type(TokenType::FatArrow), parse_expr)(rest)?;
+
+    Ok((
+        rest,
+        MatchArm {
+            pattern,
+            guard,
+            body,
+            span: consumed_span(input, rest),
+        },
+    ))
+}
+
+/// `\x, y => body` or with the parameters in parentheses `\(x: Int) => body`
+fn parse_lambda<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Expression<'b>> {
+    let (rest, _) = token_type(TokenType::BackSlash)(input)?;
+    let (rest, params) = alt((
+        parse_function_params,
+        separated_list1(token_type(TokenType::Comma), parse_single_parameter),
+    ))(rest)?;
+    let (rest, body) = preceded(token_type(TokenType::FatArrow), parse_expr)(rest)?;
+
+    Ok((
+        rest,
+        Expression::Lambda {
+            params,
+            body: Box::new(body),
+            span: consumed_span(input, rest),
+        },
+    ))
+}
+
+/// `let pattern = value` with an optional type annotation on the pattern
+fn parse_let_binding<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, LetBinding<'b>> {
+    let (rest, _) = token_type(TokenType::Let)(input)?;
+    let (rest, pattern) = parse_pattern(rest)?;
+    let (rest, typ) = match token_type(TokenType::Colon)(rest) {
+        Ok((rest, _)) => {
+            let (rest, typ) = parse_type(rest)?;
+            (rest, Some(typ))
+        }
+        Err(_) => (rest, None),
+    };
+    let (rest, _) = token_type(TokenType::Equal)(rest)?;
+    let (rest, value) = parse_expr(rest)?;
+
+    Ok((
+        rest,
+        LetBinding {
+            pattern,
+            typ,
+            value: Box::new(value),
+            span: consumed_span(input, rest),
+        },
+    ))
+}
+
+/// `{ statement; ...; result }`. A block without statements is just its result
+/// and an empty block `{}` is unit.
+///
+/// A statement that fails to parse is skipped up to the `;` or `}` ending it
+/// and replaced by `Expression::Error`, so a block only fails when it is
+/// never closed
+pub(crate) fn parse_block<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Expression<'b>> {
+    let (mut rest, _) = token_type(TokenType::LBrace)(input)?;
+
+    if let Ok((rest, _)) = token_type(TokenType::RBrace)(rest) {
+        let span = consumed_span(input, rest);
+        return Ok((rest, Expression::Unit { span }));
+    }
+
+    let mut statements = Vec::new();
+    let result = loop {
+        let (after, statement) = match parse_statement(rest) {
+            Ok((after, statement)) if ends_statement(after) => (after, statement),
+            Ok(_) | Err(nom::Err::Error(_)) => {
+                let after = skip_statement(rest);
+                let error = error_expression(rest, after);
+                (after, Statement::Expression(error))
+            }
+            Err(error) => return Err(error),
+        };
+
+        if let Ok((after, _)) = token_type(TokenType::Semicolon)(after) {
+            statements.push(statement);
+            rest = after;
+            continue;
+        }
+
+        rest = after;
+        break match statement {
+            Statement::Expression(result) => result,
+            // a `let` statement must be followed by `in` or `;`
+            Statement::Let(binding) => Expression::Error { span: binding.span },
+        };
+    };
+    let (rest, _) = token_type(TokenType::RBrace)(rest)?;
+
+    if statements.is_empty() {
+        return Ok((rest, result));
+    }
+
+    Ok((
+        rest,
+        Expression::Block {
+            statements,
+            result: Box::new(result),
+            span: consumed_span(input, rest),
+        },
+    ))
+}
+
+fn ends_statement(input: &[Token<'_>]) -> bool {
+    matches!(
+        input.first().map(|t| &t.typ),
+        Some(TokenType::Semicolon | TokenType::RBrace)
+    )
+}
+
+pub(crate) fn parse_statement<'a, 'b>(
+    input: &'a [Token<'b>],
+) -> ParseResult<'a, 'b, Statement<'b>> {
+    if input.first().map(|t| &t.typ) != Some(&TokenType::Let) {
+        let (rest, expr) = parse_expr(input)?;
+        return Ok((rest, Statement::Expression(expr)));
+    }
+
+    let (rest, binding) = parse_let_binding.context("let binding").parse(input)?;
+    let Ok((rest, _)) = token_type(TokenType::In)(rest) else {
+        return Ok((rest, Statement::Let(binding)));
+    };
+
+    // `let ... in ...` used as an expression
+    let (rest, body) = parse_expr(rest)?;
+    let span = binding.span.to(body.span());
+    Ok((
+        rest,
+        Statement::Expression(Expression::Let {
+            binding,
+            body: Box::new(body),
+            span,
+        }),
+    ))
+}
+
+/// A function followed by the arguments it is applied to, e.g. `math.log 2 3`
+fn parse_application<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Expression<'b>> {
+    let (mut input, func) = parse_postfix(input, true)?;
+
+    let mut args = Vec::new();
+    while starts_argument(input) {
+        let (rest, arg) = parse_postfix(input, false)?;
+        args.push(arg);
+        input = rest;
+    }
+    record(input, || {
+        vec![
+            Expected::Identifier,
+            Expected::Literal,
+            Expected::token(&TokenType::Lparen),
+        ]
+    });
+
+    let Some(last) = args.last() else {
+        return Ok((input, func));
+    };
+    let span = func.span().to(last.span());
+
+    Ok((
+        input,
+        Expression::Call {
+            func: Box::new(func),
+            args,
+            span,
+        },
+    ))
+}
+
+fn starts_argument(input: &[Token<'_>]) -> bool {
+    matches!(
+        input.first().map(|t| &t.typ),
+        Some(
+            TokenType::Ident(_)
+                | TokenType::IntegerLit(..)
+                | TokenType::FloatLit(..)
+                | TokenType::StringLit(_)
+                | TokenType::CharLit(_)
+                | TokenType::True
+                | TokenType::False
+                | TokenType::Lparen
+        )
+    )
+}
+
+/// An atom followed by any field accesses and, if `calls` is set, parenthesised
+/// argument lists. Calls are not allowed on juxtaposed arguments so that
+/// `f a (b)` applies `f` to both `a` and `b`
+fn parse_postfix<'a, 'b>(
+    input: &'a [Token<'b>],
+    calls: bool,
+) -> ParseResult<'a, 'b, Expression<'b>> {
+    let (mut input, mut expr) = parse_atom(input)?;
+
+    loop {
+        match input.first().map(|t| &t.typ) {
+            Some(TokenType::Dot) => {
+                let (rest, field) = ident(&input[1..])?;
+                let span = expr.span().to(input[1].span);
+                expr = Expression::Field {
+                    expr: Box::new(expr),
+                    field,
+                    span,
+                };
+                input = rest;
+            }
+
+            Some(TokenType::Lparen) if calls => {
+                let (rest, args) = comma_list(parse_expr, TokenType::RParen)(&input[1..])?;
+                let span = expr.span().to(consumed_span(input, rest));
+                expr = Expression::Call {
+                    func: Box::new(expr),
+                    args,
+                    span,
+                };
+                input = rest;
+            }
+
+            _ => break,
+        }
+    }
+    record(input, || match calls {
+        true => Expected::tokens(&[TokenType::Dot, TokenType::Lparen]),
+        false => Expected::tokens(&[TokenType::Dot]),
+    });
+
+    Ok((input, expr))
+}
+
+fn parse_atom<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Expression<'b>> {
+    match input.first().map(|t| &t.typ) {
+        Some(TokenType::Ident(_)) => parse_variable(input),
+        Some(TokenType::Lparen) => parse_parenthesised(input),
+        Some(TokenType::LBrace) if is_record(input) => parse_record.context("record").parse(input),
+        Some(TokenType::LBrace) => parse_block(input),
+        Some(_) => {
+            let (rest, value) = literal(input).map_err(|_| {
+                nom::Err::Error(TokenError::expected(
+                    input,
+                    [Expected::Identifier, Expected::Literal]
+                        .into_iter()
+                        .chain(Expected::tokens(&OPERAND_STARTS))
+                        .collect(),
+                ))
+            })?;
+            Ok((
+                rest,
+                Expression::Literal {
+                    value,
+                    span: input[0].span,
+                },
+            ))
+        }
+        None => Err(nom::Err::Error(TokenError::expected(input, Vec::new()))),
+    }
+}
+
+/// A possibly qualified name e.g. `x` or `Option::Some`
+fn parse_variable<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Expression<'b>> {
+    let (mut rest, first) = ident(input)?;
+    let mut path = vec![first];
+
+    while let Ok((after_colons, _)) = token_type(TokenType::DoubleColon)(rest) {
+        let (after_ident, segment) = ident(after_colons)?;
+        path.push(segment);
+        rest = after_ident;
+    }
+
+    Ok((
+        rest,
+        Expression::Variable {
+            path,
+            span: consumed_span(input, rest),
+        },
+    ))
+}
+
+/// `()`, `(expr)` or a tuple `(a, b, ...)`
+fn parse_parenthesised<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Expression<'b>> {
+    let (rest, _) = token_type(TokenType::Lparen)(input)?;
+    let (rest, mut elements) = comma_list(parse_expr, TokenType::RParen)(rest)?;
+    let span = consumed_span(input, rest);
+
+    let expr = match elements.len() {
+        0 => Expression::Unit { span },
+        1 => elements.remove(0),
+        _ => Expression::Tuple { elements, span },
+    };
+
+    Ok((rest, expr))
+}
+
+/// Whether the braces starting `input` contain a record rather than a block.
+/// A block cannot start with `name =`
+fn is_record(input: &[Token<'_>]) -> bool {
+    matches!(
+        input.get(1..3).map(|t| [&t[0].typ, &t[1].typ]),
+        Some([TokenType::Ident(_), TokenType::Equal])
+    )
+}
+
+/// `{ name = value, ... }` allowing a trailing comma
+fn parse_record<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Expression<'b>> {
+    let field = |input| {
+        let (input, name) = ident(input)?;
+        let (input, value) = preceded(token_type(TokenType::Equal), parse_expr)(input)?;
+        Ok((input, (name, value)))
+    };
+
+    let (rest, fields) = delimited(
+        token_type(TokenType::LBrace),
+        terminated(
+            separated_list1(token_type(TokenType::Comma), field),
+            opt(token_type(TokenType::Comma)),
+        ),
+        list_end(TokenType::RBrace),
+    )(input)?;
+
+    Ok((
+        rest,
+        Expression::Record {
+            fields,
+            span: consumed_span(input, rest),
+        },
+    ))
+}
+
+/// The span of the tokens consumed going from `input` to `rest`
+pub(crate) fn consumed_span(input: &[Token<'_>], rest: &[Token<'_>]) -> Span {
+    let consumed = &input[..input.len() - rest.len()];
+    match (consumed.first(), consumed.last()) {
+        (Some(first), Some(last)) => first.span.to(last.span),
+        _ => Span::empty(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cool_asserts::assert_matches;
+    use meri_ast::{
+        BinaryOp, Expression, Ident, LetBinding, Literal, MatchArm, NumberSuffix, Pattern,
+        Statement, TypeExpr,
+    };
+
+    use crate::{lexer::tokenize, span::Span, token::TokenType};
+
+    use super::parse_expr;
+
+    fn parse(input: &str) -> Expression<'_> {
+        let tokens = tokenize(input).collect::<Vec<_>>();
+        let (rest, expr) = parse_expr(&tokens).unwrap();
+        assert_eq!(rest[0].typ, TokenType::EOF, "unparsed input in {input:?}");
+        expr
+    }
+
+    /// Renders the structure of an expression as an s-expression
+    fn sexp(expr: &Expression) -> String {
+        match expr {
+            Expression::Unit { .. } => "()".to_string(),
+            Expression::Error { .. } => "error".to_string(),
+            Expression::Literal { value, .. } => literal_sexp(value),
+            Expression::Variable { path, .. } => path_sexp(path),
+            Expression::Tuple { elements, .. } => {
+                let elements = elements.iter().map(sexp).collect::<Vec<_>>();
+                format!("(tuple {})", elements.join(" "))
+            }
+            Expression::Call { func, args, .. } => {
+                let args = args.iter().map(|arg| format!(" {}", sexp(arg)));
+                format!("(call {}{})", sexp(func), args.collect::<String>())
+            }
+            Expression::Field { expr, field, .. } => format!("(. {} {})", sexp(expr), field.0),
+            Expression::Unary { op, expr, .. } => format!("({op} {})", sexp(expr)),
+            Expression::Binary { op, lhs, rhs, .. } => {
+                format!("({op} {} {})", sexp(lhs), sexp(rhs))
+            }
+            Expression::Let { binding, body, .. } => {
+                format!("(let {} {})", binding_sexp(binding), sexp(body))
+            }
+            Expression::If {
+                cond,
+                then_branch,
+                else_branch,
+                ..
+            } => format!(
+                "(if {} {} {})",
+                sexp(cond),
+                sexp(then_branch),
+                sexp(else_branch)
+            ),
+            Expression::Match {
+                scrutinee, arms, ..
+            } => {
+                let arms = arms.iter().map(|arm| {
+                    let pattern = pattern_sexp(&arm.pattern);
+                    match &arm.guard {
+                        Some(guard) => {
+                            format!(" [{pattern} {} {}]", sexp(guard), sexp(&arm.body))
+                        }
+                        None => format!(" [{pattern} {}]", sexp(&arm.body)),
+                    }
+                });
+                format!("(match {}{})", sexp(scrutinee), arms.collect::<String>())
+            }
+            Expression::Lambda { params, body, .. } => {
+                let params = params
+                    .iter()
+                    .map(|(pattern, _)| format!("{} ", pattern_sexp(pattern)));
+                format!("(\\ {}{})", params.collect::<String>(), sexp(body))
+            }
+            Expression::Record { fields, .. } => {
+                let fields = fields
+                    .iter()
+                    .map(|(Ident(name), value)| format!(" [{name} {}]", sexp(value)));
+                format!("(record{})", fields.collect::<String>())
+            }
+            Expression::Block {
+                statements, result, ..
+            } => {
+                let statements = statements.iter().map(|statement| match statement {
+                    Statement::Let(binding) => format!("{} ", binding_sexp(binding)),
+                    Statement::Expression(expr) => format!("{} ", sexp(expr)),
+                });
+                format!("{{{}{}}}", statements.collect::<String>(), sexp(result))
+            }
+        }
+    }
+
+    fn literal_sexp(literal: &Literal) -> String {
+        let suffix = |suffix: Option<NumberSuffix>| suffix.map_or(String::new(), |s| s.to_string());
+        match literal {
+            Literal::Int(int, int_suffix) => format!("{int}{}", suffix(*int_suffix)),
+            Literal::Float(float, float_suffix) => format!("{float}{}", suffix(*float_suffix)),
+            Literal::String(string) => format!("{string:?}"),
+            Literal::Char(c) => format!("{c:?}"),
+            Literal::Bool(b) => b.to_string(),
+        }
+    }
+
+    fn path_sexp(path: &[Ident]) -> String {
+        path.iter()
+            .map(|Ident(ident)| *ident)
+            .collect::<Vec<_>>()
+            .join("::")
+    }
+
+    /// Renders a pattern, with a plain binding rendered as its name
+    fn pattern_sexp(pattern: &Pattern) -> String {
+        let list = |patterns: &[Pattern]| {
+            patterns
+                .iter()
+                .map(|pattern| format!(" {}", pattern_sexp(pattern)))
+                .collect::<String>()
+        };
+        match pattern {
+            Pattern::Wildcard => "_".to_string(),
+            Pattern::Binding(Ident(name)) => name.to_string(),
+            Pattern::Literal(literal) => literal_sexp(literal),
+            Pattern::DataVariant { path, fields } if fields.is_empty() => path_sexp(path),
+            Pattern::DataVariant { path, fields } => {
+                format!("({}{})", path_sexp(path), list(fields))
+            }
+            Pattern::Record { fields, rest } => {
+                let fields = fields
+                    .iter()
+                    .map(|(Ident(name), pattern)| format!(" [{name} {}]", pattern_sexp(pattern)));
+                let rest = if *rest { " .." } else { "" };
+                format!("(record{}{rest})", fields.collect::<String>())
+            }
+            Pattern::Tuple(elements) => format!("(tuple{})", list(elements)),
+            Pattern::Or(alternatives) => format!("(|{})", list(alternatives)),
+            Pattern::As {
+                pattern,
+                ident: Ident(name),
+            } => format!("(as {} {name})", pattern_sexp(pattern)),
+        }
+    }
+
+    fn binding_sexp(binding: &LetBinding) -> String {
+        format!(
+            "[{} {}]",
+            pattern_sexp(&binding.pattern),
+            sexp(&binding.value)
+        )
+    }
+
+    #[test]
+    fn literals_and_variables() {
+        assert_eq!(sexp(&parse("42")), "42");
+        assert_eq!(sexp(&parse("\"Hujambo\"")), "\"Hujambo\"");
+        assert_eq!(sexp(&parse("true")), "true");
+        assert_eq!(sexp(&parse("x")), "x");
+        assert_eq!(sexp(&parse("Option::Some")), "Option::Some");
+        assert_eq!(sexp(&parse("()")), "()");
+    }
+
+    #[test]
+    fn typed_literals() {
+        assert_eq!(
+            parse("10u8"),
+            Expression::Literal {
+                value: Literal::Int(10, Some(NumberSuffix::U8)),
+                span: Span::new(0, 3)
+            }
+        );
+        assert_eq!(sexp(&parse("3.0f32 * 2.5")), "(* 3f32 2.5)");
+        assert_eq!(sexp(&parse("n-1i64")), "(- n 1i64)");
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(sexp(&parse("a + b * c")), "(+ a (* b c))");
+        assert_eq!(sexp(&parse("a * b + c")), "(+ (* a b) c)");
+        assert_eq!(sexp(&parse("a - b - c")), "(- (- a b) c)");
+        assert_eq!(sexp(&parse("a / b % c")), "(% (/ a b) c)");
+        assert_eq!(
+            sexp(&parse("a + 1 == b && c < d || e")),
+            "(|| (&& (== (+ a 1) b) (< c d)) e)"
+        );
+        assert_eq!(sexp(&parse("xs |> f |> g")), "(|> (|> xs f) g)");
+    }
+
+    #[test]
+    fn power_is_right_associative() {
+        assert_eq!(sexp(&parse("a ^ b ^ c")), "(^ a (^ b c))");
+        assert_eq!(sexp(&parse("2 * x ^ 2")), "(* 2 (^ x 2))");
+    }
+
+    #[test]
+    fn unary_operators() {
+        assert_eq!(sexp(&parse("-x ^ 2")), "(- (^ x 2))");
+        assert_eq!(sexp(&parse("-x * y")), "(* (- x) y)");
+        assert_eq!(sexp(&parse("!a && b")), "(&& (! a) b)");
+        assert_eq!(sexp(&parse("- -x")), "(- (- x))");
+        assert_eq!(sexp(&parse("x ^ -1")), "(^ x -1)");
+        assert_eq!(sexp(&parse("-2 ^ 2")), "(- (^ 2 2))");
+        assert_eq!(sexp(&parse("-2 * 2")), "(* -2 2)");
+        assert_eq!(
+            parse("-1.5f32"),
+            Expression::Literal {
+                value: Literal::Float(-1.5, Some(NumberSuffix::F32)),
+                span: Span::new(0, 6)
+            }
+        );
+    }
+
+    #[test]
+    fn negative_literal_after_operand_is_subtraction() {
+        assert_eq!(sexp(&parse("n-1")), "(- n 1)");
+        assert_eq!(sexp(&parse("n -1 * 2")), "(- n (* 1 2))");
+        assert_eq!(sexp(&parse("f(n-1)")), "(call f (- n 1))");
+        assert_eq!(sexp(&parse("{ n-1u8 }")), "(- n 1u8)");
+        assert_eq!(sexp(&parse("x-1u32 + 2")), "(+ (- x 1u32) 2)");
+        assert_eq!(sexp(&parse("-1")), "-1");
+    }
+
+    #[test]
+    fn parentheses_and_tuples() {
+        assert_eq!(sexp(&parse("(a + b) * c")), "(* (+ a b) c)");
+        assert_eq!(sexp(&parse("(a, b + 1)")), "(tuple a (+ b 1))");
+    }
+
+    #[test]
+    fn application() {
+        assert_eq!(sexp(&parse("math.log 2 3")), "(call (. math log) 2 3)");
+        assert_eq!(sexp(&parse("f(x)")), "(call f x)");
+        assert_eq!(sexp(&parse("f(x, y)")), "(call f x y)");
+        assert_eq!(sexp(&parse("f()")), "(call f)");
+        assert_eq!(sexp(&parse("f a (b + c)")), "(call f a (+ b c))");
+        assert_eq!(sexp(&parse("f a + g b")), "(+ (call f a) (call g b))");
+        assert_eq!(sexp(&parse("Option::Some 0")), "(call Option::Some 0)");
+        assert_eq!(
+            sexp(&parse("Stdio.print(\"Hello World\")")),
+            "(call (. Stdio print) \"Hello World\")"
+        );
+    }
+
+    #[test]
+    fn field_access() {
+        assert_eq!(sexp(&parse("rect.size.width")), "(. (. rect size) width)");
+        assert_eq!(sexp(&parse("f rect.size")), "(call f (. rect size))");
+        assert_eq!(sexp(&parse("(f x).y")), "(. (call f x) y)");
+    }
+
+    #[test]
+    fn spans() {
+        let expr = parse("f (a) + 10");
+        assert_eq!(expr.span(), Span::new(0, 9));
+
+        let Expression::Binary { op, lhs, rhs, .. } = expr else {
+            panic!("expected a binary expression");
+        };
+        assert_eq!(op, BinaryOp::Add);
+        assert_eq!(lhs.span(), Span::new(0, 4));
+        assert_eq!(
+            *rhs,
+            Expression::Literal {
+                value: Literal::Int(10, None),
+                span: Span::new(8, 9)
+            }
+        );
+
+        let Expression::Binary { rhs, .. } = parse("n-12") else {
+            panic!("expected a binary expression");
+        };
+        assert_eq!(rhs.span(), Span::new(2, 3));
+    }
+
+    #[test]
+    fn let_in() {
+        assert_eq!(sexp(&parse("let x = 1 in x + 1")), "(let [x 1] (+ x 1))");
+        assert_eq!(
+            sexp(&parse("let x = 1 in let y = x in y")),
+            "(let [x 1] (let [y x] y))"
+        );
+        assert_eq!(sexp(&parse("2 * let x = 1 in x")), "(* 2 (let [x 1] x))");
+    }
+
+    #[test]
+    fn let_with_type_annotation() {
+        assert_matches!(
+            parse("let x: Int = 1 in x"),
+            Expression::Let {
+                binding: LetBinding {
+                    pattern: Pattern::Binding(Ident("x")),
+                    typ: Some(TypeExpr::Named { .. }),
+                    span,
+                    ..
+                },
+                ..
+            } => assert_eq!(span, Span::new(0, 13))
+        );
+    }
+
+    #[test]
+    fn blocks() {
+        assert_eq!(sexp(&parse("{ x }")), "x");
+        assert_eq!(
+            parse("{ }"),
+            Expression::Unit {
+                span: Span::new(0, 2)
+            }
+        );
+        assert_eq!(
+            sexp(&parse("{ let x = 1; let y: Int = x * 2; print y; x + y }")),
+            "{[x 1] [y (* x 2)] (call print y) (+ x y)}"
+        );
+        assert_eq!(
+            sexp(&parse("{ let x = 1; { let x = 2; x } + x }")),
+            "{[x 1] (+ {[x 2] x} x)}"
+        );
+        assert_eq!(sexp(&parse("{ let x = 1 in x; 2 }")), "{(let [x 1] x) 2}");
+        assert_eq!(parse("{ a; b }").span(), Span::new(0, 7));
+    }
+
+    #[test]
+    fn block_needs_result() {
+        assert_eq!(sexp(&parse("{ let x = 1 }")), "error");
+        assert_eq!(sexp(&parse("{ let x = 1; }")), "{[x 1] error}");
+        assert_eq!(sexp(&parse("{ a; }")), "{a error}");
+        assert_eq!(sexp(&parse("{ ; }")), "{error error}");
+    }
+
+    #[test]
+    fn recovers_broken_statements() {
+        let expr = parse("{ let x = ; f (x +) y; x }");
+        assert_eq!(sexp(&expr), "{error error x}");
+        assert_matches!(expr, Expression::Block { statements, .. } => {
+            assert_matches!(
+                &statements[..],
+                [
+                    Statement::Expression(Expression::Error { span: first }),
+                    Statement::Expression(Expression::Error { span: second }),
+                ] => {
+                    assert_eq!(*first, Span::new(2, 8));
+                    assert_eq!(*second, Span::new(12, 20));
+                }
+            );
+        });
+    }
+
+    #[test]
+    fn unclosed_block() {
+        let tokens = tokenize("{ a; b").collect::<Vec<_>>();
+        assert!(parse_expr(&tokens).is_err());
+    }
+
+    #[test]
+    fn if_then_else() {
+        assert_eq!(sexp(&parse("if a < b then a else b")), "(if (< a b) a b)");
+        assert_eq!(
+            sexp(&parse("if a then b else if c then d else e + 1")),
+            "(if a b (if c d (+ e 1)))"
+        );
+        assert_eq!(sexp(&parse("1 + if a then 2 else 3")), "(+ 1 (if a 2 3))");
+        assert_eq!(parse("if a then b else c").span(), Span::new(0, 17));
+    }
+
+    #[test]
+    fn if_needs_else() {
+        let tokens = tokenize("if a then b").collect::<Vec<_>>();
+        assert!(parse_expr(&tokens).is_err());
+    }
+
+    #[test]
+    fn match_arms() {
+        assert_eq!(
+            sexp(&parse("match f x { y => y + 1, z => 0 }")),
+            "(match (call f x) [y (+ y 1)] [z 0])"
+        );
+        assert_eq!(
+            sexp(&parse("match x { n if n > 0 => n, n => -n, }")),
+            "(match x [n (> n 0) n] [n (- n)])"
+        );
+        assert_eq!(sexp(&parse("match x {}")), "(match x)");
+    }
+
+    #[test]
+    fn broken_match_arm() {
+        let tokens = tokenize("match x { y => 1, z => }").collect::<Vec<_>>();
+        let Err(nom::Err::Error(error)) = parse_expr(&tokens) else {
+            panic!("expected the broken arm to fail");
+        };
+        let error = error.into_parse_error();
+        assert_eq!(error.span, Span::new(23, 23));
+        assert_eq!(error.context, ["match expression", "match arm"]);
+    }
+
+    #[test]
+    fn match_destructuring_arms() {
+        assert_matches!(
+            parse("match c { Color::Rgb(r, _, _) | Color::Hsv { a: r, .. } => r, _ => 0 }"),
+            Expression::Match { arms, .. } => {
+                assert_matches!(&arms[0].pattern, Pattern::Or(alternatives) => {
+                    assert_eq!(alternatives.len(), 2);
+                });
+                assert_eq!(arms[1].pattern, Pattern::Wildcard);
+            }
+        );
+        assert_eq!(
+            sexp(&parse(
+                "match c { Color::Rgb(r, _, _) | Color::Hsv { a: r, .. } => r, None as n => 0 }"
+            )),
+            "(match c [(| (Color::Rgb r _ _) (Color::Hsv (record [a r] ..))) r] [(as None n) 0])"
+        );
+        assert_eq!(
+            sexp(&parse("{ let (a, 1) = p; \\{ x }, 'c' => a }")),
+            "{[(tuple a 1) p] (\\ (record [x x]) 'c' a)}"
+        );
+    }
+
+    #[test]
+    fn match_arm_spans() {
+        assert_matches!(
+            parse("match x { n if n > 0 => n }"),
+            Expression::Match { arms, span, .. } => {
+                assert_eq!(span, Span::new(0, 26));
+                assert_matches!(
+                    &arms[..],
+                    [MatchArm { pattern: Pattern::Binding(Ident("n")), guard: Some(_), span, .. }]
+                        => assert_eq!(*span, Span::new(10, 24))
+                );
+            }
+        );
+    }
+
+    #[test]
+    fn lambdas() {
+        assert_eq!(sexp(&parse("\\x, y => x + y")), "(\\ x y (+ x y))");
+        assert_eq!(
+            sexp(&parse("apply 1 (\\x => x * 2) xs")),
+            "(call apply 1 (\\ x (* x 2)) xs)"
+        );
+        assert_eq!(sexp(&parse("\\x => \\y => x")), "(\\ x (\\ y x))");
+    }
+
+    #[test]
+    fn typed_lambda() {
+        assert_matches!(
+            parse("\\(x: Int, y) => x"),
+            Expression::Lambda { params, span, .. } => {
+                assert_eq!(span, Span::new(0, 16));
+                assert_eq!(
+                    params,
+                    [
+                        (
+                            Pattern::Binding(Ident("x")),
+                            Some(TypeExpr::Named { path: vec![Ident("Int")], args: vec![] })
+                        ),
+                        (Pattern::Binding(Ident("y")), None)
+                    ]
+                );
+            }
+        );
+    }
+
+    #[test]
+    fn lambda_needs_params() {
+        let tokens = tokenize("\\ => x").collect::<Vec<_>>();
+        assert!(parse_expr(&tokens).is_err());
+    }
+
+    #[test]
+    fn records() {
+        assert_eq!(
+            sexp(&parse("{ size = 0, length = n + 1, }")),
+            "(record [size 0] [length (+ n 1)])"
+        );
+        assert_eq!(sexp(&parse("{ { x = 1 } }")), "(record [x 1])");
+        assert_eq!(parse("{ x = 1 }").span(), Span::new(0, 8));
+    }
+
+    #[test]
+    fn rejects_dangling_operator() {
+        let tokens = tokenize("a +").collect::<Vec<_>>();
+        assert!(parse_expr(&tokens).is_err());
+    }
+}
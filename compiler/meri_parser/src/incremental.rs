@@ -0,0 +1,236 @@
+//! Incremental re-lexing of edited source.
+//!
+//! Rather than tokenizing a whole file after every keystroke, only the tokens
+//! around an edit are lexed again. Lexing restarts at a token boundary before the
+//! edit and stops as soon as a newly lexed token starts where one of the old
+//! tokens following the edit did. Since the lexer carries no state between tokens,
+//! the remaining old tokens are then known to be unchanged apart from their position.
+
+use std::ops::Range;
+
+use crate::{
+    lexer::Lexer,
+    span::Span,
+    token::{Token, TokenType},
+};
+
+/// Replacement of a range of the source text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit<'e> {
+    /// Byte range of the replaced text in the source before the edit
+    pub range: Range<usize>,
+    pub replacement: &'e str,
+}
+
+/// The tokens of an edited source
+#[derive(Debug, Clone, PartialEq)]
+pub struct Relexed<'a> {
+    pub tokens: Vec<Token<'a>>,
+    /// Indices of the tokens from before the edit that were replaced
+    pub old_range: Range<usize>,
+    /// Indices of the tokens in `tokens` that replaced them
+    pub new_range: Range<usize>,
+}
+
+/// How far past the end of a token the lexer may look across whitespace while
+/// deciding where the token ends. A line comment checks for a following `\r\n`
+const LOOKAHEAD: usize = 2;
+
+/// Updates `old_tokens`, the tokens of the source before `edit`, to the tokens
+/// of `input`, the source after the edit. `old_tokens` must be the complete
+/// output of `tokenize` including the `EOF` token
+pub fn relex<'a>(old_tokens: &[Token<'_>], edit: &TextEdit<'_>, input: &'a str) -> Relexed<'a> {
+    let delta = edit.replacement.len() as isize - edit.range.len() as isize;
+
+    // The first token that could be affected by the edit. Tokens directly
+    // adjacent to it could have been ended by looking at its text, e.g. the `r`
+    // in `r#x` that would start a raw string if `x` became `"`
+    let mut first = old_tokens
+        .iter()
+        .position(|token| token.span.end + LOOKAHEAD >= edit.range.start)
+        .unwrap_or(old_tokens.len().saturating_sub(1));
+    while first > 0 && old_tokens[first - 1].span.end + 1 == old_tokens[first].span.start {
+        first -= 1;
+    }
+
+    // The end of the last token kept is always a char boundary followed by
+    // whitespace, unlike the start of `EOF` which may be inside a comment
+    let restart = first
+        .checked_sub(1)
+        .map_or(0, |previous| old_tokens[previous].span.end + 1)
+        .min(edit.range.start);
+
+    let mut lexer = Lexer::starting_at(input, restart);
+    let mut new_tokens = Vec::new();
+    let mut old_end = first;
+    loop {
+        let token = lexer.advance_token();
+        if token.typ == TokenType::EOF {
+            new_tokens.push(token);
+            old_end = old_tokens.len();
+            break;
+        }
+
+        // skip old tokens that start before the new token in the edited source
+        while old_tokens.get(old_end).is_some_and(|old| {
+            old.typ != TokenType::EOF
+                && (old.span.start < edit.range.end
+                    || old.span.start.wrapping_add_signed(delta) < token.span.start)
+        }) {
+            old_end += 1;
+        }
+
+        let resynced = old_tokens.get(old_end).is_some_and(|old| {
+            old.typ != TokenType::EOF
+                && old.span.start.wrapping_add_signed(delta) == token.span.start
+        });
+        if resynced {
+            break;
+        }
+
+        new_tokens.push(token);
+    }
+
+    let new_range = first..first + new_tokens.len();
+    let tokens = old_tokens[..first]
+        .iter()
+        .map(|token| rebase(token, input, 0))
+        .chain(new_tokens)
+        .chain(
+            old_tokens[old_end..]
+                .iter()
+                .map(|token| rebase(token, input, delta)),
+        )
+        .collect();
+
+    Relexed {
+        tokens,
+        old_range: first..old_end,
+        new_range,
+    }
+}
+
+/// Moves an unchanged token by `delta` bytes, borrowing its text from `input`
+fn rebase<'a>(token: &Token<'_>, input: &'a str, delta: isize) -> Token<'a> {
+    let span = Span::new(
+        token.span.start.wrapping_add_signed(delta),
+        token.span.end.wrapping_add_signed(delta),
+    );
+    // `EOF` has no text
+    let text = input.get(span.start..=span.end).unwrap_or("");
+
+    Token {
+        typ: token.typ.with_source(text),
+        span,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use crate::{
+        lexer::tokenize,
+        span::Span,
+        token::{Token, TokenType},
+    };
+
+    use super::{relex, Relexed, TextEdit};
+
+    /// Applies `edit` to `source` and relexes it, checking the result against
+    /// tokenizing the edited source from scratch
+    fn check_edit(
+        source: &str,
+        range: std::ops::Range<usize>,
+        replacement: &str,
+    ) -> Relexed<'static> {
+        let old_tokens = tokenize(source).collect::<Vec<_>>();
+        let mut edited = source.to_string();
+        edited.replace_range(range.clone(), replacement);
+        let edited: &'static str = Box::leak(edited.into_boxed_str());
+
+        let edit = TextEdit { range, replacement };
+        let relexed = relex(&old_tokens, &edit, edited);
+        assert_eq!(relexed.tokens, tokenize(edited).collect::<Vec<_>>());
+        relexed
+    }
+
+    #[test]
+    fn edit_inside_ident() {
+        let relexed = check_edit("a = foo + bar", 5..6, "ooo");
+        assert_eq!(relexed.old_range, 2..3);
+        assert_eq!(relexed.new_range, 2..3);
+        assert_eq!(
+            relexed.tokens[2],
+            Token {
+                typ: TokenType::Ident("foooo"),
+                span: Span::new(4, 8)
+            }
+        );
+        assert_eq!(
+            relexed.tokens[4],
+            Token {
+                typ: TokenType::Ident("bar"),
+                span: Span::new(12, 14)
+            }
+        );
+    }
+
+    #[test]
+    fn edit_merging_tokens() {
+        let relexed = check_edit("a = > b", 3..4, "");
+        assert_eq!(relexed.tokens[1].typ, TokenType::FatArrow);
+    }
+
+    #[test]
+    fn edit_opening_string() {
+        // the rest of the line becomes part of the string
+        let relexed = check_edit("a b c\nd", 2..2, "\"");
+        assert_eq!(relexed.old_range, 0..3);
+        assert_eq!(relexed.new_range, 0..2);
+        assert_eq!(relexed.tokens[2].typ, TokenType::Ident("d"));
+    }
+
+    #[test]
+    fn edit_extending_block_comment() {
+        check_edit("a {- x -} b c", 7..9, "");
+    }
+
+    #[test]
+    fn edit_changing_raw_string_prefix() {
+        check_edit("y r#x", 4..5, "\"z\"#");
+    }
+
+    #[test]
+    fn edit_at_end() {
+        check_edit("a b", 3..3, " c");
+        check_edit("a b", 0..3, "");
+    }
+
+    #[test]
+    fn edit_after_multi_byte_char() {
+        check_edit("a\u{3000}", 4..4, "b");
+        check_edit("a ¿", 4..4, "¿");
+        check_edit("a -- ¿", 7..7, "b");
+    }
+
+    proptest! {
+        #[test]
+        fn relex_matches_tokenize(
+            source in r#"([a-z_]{1,4}|[0-9][0-9.e_]{0,3}|"[^"\n]{0,3}"?|--[^\n]{0,3}|\{-|-\}|[ \r\n\u{3000}]{1,2}|[(){}=<>:.#'¿-])*"#,
+            replacement in r#"([a-z]{1,3}|[0-9]|"|--|\{-|-\}|[ \r\n\u{3000}]|[(){}=<>:.#'¿-]){0,3}"#,
+            start in any::<prop::sample::Index>(),
+            len in 0usize..6,
+        ) {
+            let floor = |mut idx: usize| {
+                while !source.is_char_boundary(idx) {
+                    idx -= 1;
+                }
+                idx
+            };
+            let start = floor(start.index(source.len() + 1));
+            let end = floor((start + len).min(source.len()));
+            check_edit(&source, start..end, &replacement);
+        }
+    }
+}
@@ -0,0 +1,267 @@
+//! Layout sensitive lexing.
+//!
+//! Records where every token sits in the layout of the source and optionally
+//! inserts virtual `Indent`, `Dedent` and `Newline` tokens following the offside
+//! rule so that blocks can be delimited by indentation rather than braces.
+//! Inside parentheses, braces and brackets the layout is ignored, so
+//! ```meri
+//! record Wow =
+//!   { first_name: String
+//!   , age: Int
+//!   }
+//! ```
+//! only produces a single `Indent` before the `{`.
+
+use crate::{
+    lexer::tokenize,
+    span::{SourceMap, Span},
+    token::{Token, TokenType},
+};
+
+/// The position of a token in the layout of the source. All values are zero based
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    /// Line the token starts on
+    pub line: usize,
+    /// Column the token starts at
+    pub column: usize,
+    /// Column of the first token on the line the token starts on
+    pub indent: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutToken<'a> {
+    pub token: Token<'a>,
+    pub layout: Layout,
+}
+
+/// Tokenizes the input recording the layout of every token. When
+/// `virtual_tokens` is set, `Indent`, `Dedent` and `Newline` tokens are inserted
+/// before the first token of every line according to its indentation. These
+/// share the span and layout of the token they precede.
+///
+/// Comments, including doc comments, do not affect the layout
+pub fn tokenize_with_layout(input: &str, virtual_tokens: bool) -> Vec<LayoutToken<'_>> {
+    with_layout(input, tokenize(input), virtual_tokens)
+}
+
+/// Records the layout of `lexed`, the tokens of `input` up to and including
+/// `EOF`, as `tokenize_with_layout` does
+pub(crate) fn with_layout<'a>(
+    input: &'a str,
+    lexed: impl IntoIterator<Item = Token<'a>>,
+    virtual_tokens: bool,
+) -> Vec<LayoutToken<'a>> {
+    let source_map = SourceMap::new(input);
+    let mut tokens = Vec::new();
+
+    // indentation of the enclosing blocks, innermost last
+    let mut indents = vec![0];
+    // how deeply nested in brackets the current token is
+    let mut nesting = 0usize;
+    let mut current_line = None;
+    let mut line_indent = 0;
+
+    for token in lexed {
+        let location = source_map.location(token.span.start);
+        let is_comment = matches!(token.typ, TokenType::Comment(_) | TokenType::DocComment(_));
+        let starts_line = !is_comment && current_line != Some(location.line);
+        let is_first = current_line.is_none();
+
+        if starts_line {
+            current_line = Some(location.line);
+            line_indent = location.column;
+        }
+
+        let layout = Layout {
+            line: location.line,
+            column: location.column,
+            indent: if is_comment && current_line != Some(location.line) {
+                location.column
+            } else {
+                line_indent
+            },
+        };
+
+        if virtual_tokens && token.typ == TokenType::EOF {
+            for _ in 1..indents.len() {
+                tokens.push(virtual_token(TokenType::Dedent, token.span, layout));
+            }
+        } else if virtual_tokens && starts_line && !is_first && nesting == 0 {
+            let column = location.column;
+            let mut top = *indents.last().expect("the top level block is never closed");
+
+            while column < top && indents.len() > 1 {
+                indents.pop();
+                tokens.push(virtual_token(TokenType::Dedent, token.span, layout));
+                top = *indents.last().expect("the top level block is never closed");
+            }
+
+            if column > top {
+                indents.push(column);
+                tokens.push(virtual_token(TokenType::Indent, token.span, layout));
+            } else {
+                tokens.push(virtual_token(TokenType::Newline, token.span, layout));
+            }
+        }
+
+        match token.typ {
+            TokenType::Lparen | TokenType::LBrace | TokenType::LBracket => nesting += 1,
+            TokenType::RParen | TokenType::RBrace | TokenType::RBracket => {
+                nesting = nesting.saturating_sub(1)
+            }
+            _ => {}
+        }
+
+        tokens.push(LayoutToken { token, layout });
+    }
+
+    tokens
+}
+
+fn virtual_token(typ: TokenType<'_>, span: Span, layout: Layout) -> LayoutToken<'_> {
+    LayoutToken {
+        token: Token { typ, span },
+        layout,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::token::TokenType;
+
+    use super::{tokenize_with_layout, Layout};
+
+    fn token_types(input: &str) -> Vec<TokenType<'_>> {
+        tokenize_with_layout(input, true)
+            .into_iter()
+            .map(|t| t.token.typ)
+            .collect()
+    }
+
+    #[test]
+    fn records_layout() {
+        let tokens = tokenize_with_layout("a =\n  b c\n", false);
+        let layouts = tokens.iter().map(|t| t.layout).collect::<Vec<_>>();
+        assert_eq!(
+            layouts[0..4],
+            [
+                Layout {
+                    line: 0,
+                    column: 0,
+                    indent: 0
+                },
+                Layout {
+                    line: 0,
+                    column: 2,
+                    indent: 0
+                },
+                Layout {
+                    line: 1,
+                    column: 2,
+                    indent: 2
+                },
+                Layout {
+                    line: 1,
+                    column: 4,
+                    indent: 2
+                },
+            ]
+        );
+        assert!(tokens
+            .iter()
+            .all(|t| !matches!(t.token.typ, TokenType::Indent | TokenType::Newline)));
+    }
+
+    #[test]
+    fn indented_blocks() {
+        let inp = "f =\n  let\n    a = 1\n    b = 2\n  in a\ng = 1";
+        assert_eq!(
+            token_types(inp),
+            [
+                TokenType::Ident("f"),
+                TokenType::Equal,
+                TokenType::Indent,
+                TokenType::Let,
+                TokenType::Indent,
+                TokenType::Ident("a"),
+                TokenType::Equal,
+                TokenType::IntegerLit(1, None),
+                TokenType::Newline,
+                TokenType::Ident("b"),
+                TokenType::Equal,
+                TokenType::IntegerLit(2, None),
+                TokenType::Dedent,
+                TokenType::Newline,
+                TokenType::In,
+                TokenType::Ident("a"),
+                TokenType::Dedent,
+                TokenType::Newline,
+                TokenType::Ident("g"),
+                TokenType::Equal,
+                TokenType::IntegerLit(1, None),
+                TokenType::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn dedent_at_end_of_input() {
+        let inp = "f =\n  g\n    h";
+        assert_eq!(
+            token_types(inp),
+            [
+                TokenType::Ident("f"),
+                TokenType::Equal,
+                TokenType::Indent,
+                TokenType::Ident("g"),
+                TokenType::Indent,
+                TokenType::Ident("h"),
+                TokenType::Dedent,
+                TokenType::Dedent,
+                TokenType::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn layout_ignored_in_brackets() {
+        let inp = "record Wow =\n  { first_name: String\n  , age: Int\n  }\n";
+        assert_eq!(
+            token_types(inp),
+            [
+                TokenType::Record,
+                TokenType::Ident("Wow"),
+                TokenType::Equal,
+                TokenType::Indent,
+                TokenType::LBrace,
+                TokenType::Ident("first_name"),
+                TokenType::Colon,
+                TokenType::Ident("String"),
+                TokenType::Comma,
+                TokenType::Ident("age"),
+                TokenType::Colon,
+                TokenType::Ident("Int"),
+                TokenType::RBrace,
+                TokenType::Dedent,
+                TokenType::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn comments_do_not_affect_layout() {
+        let inp = "a\n      -- far away\n--- about b\nb";
+        assert_eq!(
+            token_types(inp),
+            [
+                TokenType::Ident("a"),
+                TokenType::Comment("-- far away"),
+                TokenType::DocComment("about b"),
+                TokenType::Newline,
+                TokenType::Ident("b"),
+                TokenType::EOF,
+            ]
+        );
+    }
+}
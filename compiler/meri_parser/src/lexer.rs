@@ -1,12 +1,11 @@
-use core::panic;
-use std::{iter::Peekable, marker::PhantomData, str::Chars};
+use std::{fmt::Display, iter::Peekable, str::Chars};
 
 use crate::{
     span::Span,
-    token::{try_into_keyword, Token, TokenType},
+    token::{try_into_keyword, NumberSuffix, Token, TokenType},
 };
 
-pub fn tokenize(input: &str) -> impl Iterator<Item = Token> + '_ {
+pub fn tokenize(input: &str) -> impl Iterator<Item = Token<'_>> + '_ {
     let mut lexer = Lexer::new(input);
     let mut done = false;
 
@@ -25,11 +24,184 @@ pub fn tokenize(input: &str) -> impl Iterator<Item = Token> + '_ {
     })
 }
 
+/// Tokenizes the whole input, collecting every error encountered on the way.
+/// Erroneous input is represented by a `TokenType::Error` token in the token
+/// stream and lexing continues after it
+pub fn tokenize_with_errors(input: &str) -> (Vec<Token<'_>>, Vec<LexError>) {
+    let mut lexer = Lexer::new(input);
+    let mut tokens = Vec::new();
+
+    loop {
+        let token = lexer.advance_token();
+        let done = token.typ == TokenType::EOF;
+        tokens.push(token);
+        if done {
+            break;
+        }
+    }
+
+    (tokens, lexer.errors)
+}
+
+/// Tokenizes the input without discarding anything. Whitespace and comments
+/// are attached as leading trivia to the token following them, with trivia at
+/// the end of the input attached to `EOF`. Concatenating the trivia and text of
+/// every token reproduces the input exactly
+pub fn tokenize_lossless(input: &str) -> Vec<LosslessToken<'_>> {
+    let mut tokens = Vec::new();
+    let mut trivia = Vec::new();
+    // byte offset just past the last token or trivia
+    let mut pos = 0;
+
+    for token in tokenize(input) {
+        if token.typ == TokenType::EOF {
+            whitespace_trivia(input, pos, input.len(), &mut trivia);
+            tokens.push(LosslessToken {
+                leading_trivia: trivia,
+                token,
+                text: "",
+            });
+            break;
+        }
+
+        whitespace_trivia(input, pos, token.span.start, &mut trivia);
+        pos = token.span.end + 1;
+        let text = &input[token.span.start..pos];
+
+        if let TokenType::Comment(_) = token.typ {
+            trivia.push(Trivia {
+                kind: TriviaKind::Comment,
+                text,
+                span: token.span,
+            });
+        } else {
+            tokens.push(LosslessToken {
+                leading_trivia: std::mem::take(&mut trivia),
+                token,
+                text,
+            });
+        }
+    }
+
+    tokens
+}
+
+/// Splits the whitespace between `start` and `end` into newline and whitespace trivia
+fn whitespace_trivia<'a>(input: &'a str, start: usize, end: usize, trivia: &mut Vec<Trivia<'a>>) {
+    let mut offset = start;
+    while offset < end {
+        let rest = &input[offset..end];
+        debug_assert!(rest.starts_with(char::is_whitespace));
+
+        let (kind, len) = if rest.starts_with('\n') {
+            (TriviaKind::Newline, 1)
+        } else if rest.starts_with("\r\n") {
+            (TriviaKind::Newline, 2)
+        } else {
+            let len = rest.find('\n').map_or(rest.len(), |idx| {
+                if rest[..idx].ends_with('\r') {
+                    idx - 1
+                } else {
+                    idx
+                }
+            });
+            (TriviaKind::Whitespace, len)
+        };
+
+        trivia.push(Trivia {
+            kind,
+            text: &rest[..len],
+            span: Span::new(offset, offset + len - 1),
+        });
+        offset += len;
+    }
+}
+
+/// A token along with the source text it was lexed from and the trivia before it
+#[derive(Debug, Clone, PartialEq)]
+pub struct LosslessToken<'a> {
+    pub leading_trivia: Vec<Trivia<'a>>,
+    pub token: Token<'a>,
+    /// The source text of the token. Empty for `EOF`
+    pub text: &'a str,
+}
+
+/// Source text that has no meaning to the parser
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trivia<'a> {
+    pub kind: TriviaKind,
+    pub text: &'a str,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    /// A run of whitespace that is not a line break
+    Whitespace,
+    /// A single `\n` or `\r\n`
+    Newline,
+    /// A line or block comment. Doc comments are kept as tokens
+    Comment,
+}
+
+/// An error encountered while tokenizing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    pub span: Span,
+}
+
+/// The different kinds of errors that can occur during tokenization
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LexErrorKind {
+    /// A character that does not start any token
+    UnexpectedChar(char),
+    /// A string literal missing its closing quote
+    UnterminatedString,
+    /// A `{-` block comment missing its closing `-}`
+    UnterminatedBlockComment,
+    /// An integer literal too large to be represented by its type
+    IntegerOverflow,
+    /// A float literal too large to be represented by its type
+    FloatOverflow,
+    /// A numeric literal with a base prefix but no digits e.g. `0x` or with
+    /// digits not valid in its base e.g. `0b12`
+    InvalidNumber,
+    /// An unknown type suffix on a numeric literal e.g. `10i7`
+    InvalidNumberSuffix,
+    /// A char literal that is empty or holds more than a single char e.g. `'ab'`
+    InvalidCharLiteral,
+    /// An unknown escape sequence or a malformed `\u{...}` escape in a string literal
+    InvalidEscape,
+}
+
+impl Display for LexErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use LexErrorKind::*;
+
+        match self {
+            UnexpectedChar(c) => write!(f, "unexpected character '{c}'"),
+            UnterminatedString => write!(f, "unterminated string literal"),
+            UnterminatedBlockComment => write!(f, "unterminated block comment"),
+            IntegerOverflow => write!(f, "integer literal is out of range for its type"),
+            FloatOverflow => write!(f, "float literal is out of range for its type"),
+            InvalidNumber => write!(f, "invalid digits in numeric literal"),
+            InvalidNumberSuffix => write!(f, "invalid numeric literal suffix"),
+            InvalidEscape => write!(f, "invalid escape sequence"),
+            InvalidCharLiteral => write!(f, "char literals must contain exactly one character"),
+        }
+    }
+}
+
 #[derive(Debug)]
-struct Lexer<'a> {
+pub(crate) struct Lexer<'a> {
     input: &'a str,
     chars: Peekable<Chars<'a>>,
+    /// Byte offset of the next char
     idx: usize,
+    /// Byte offset of the first char of the token being lexed
+    tok_start: usize,
+    errors: Vec<LexError>,
 }
 
 impl<'a> Lexer<'a> {
@@ -38,6 +210,21 @@ impl<'a> Lexer<'a> {
             input,
             chars: input.chars().peekable(),
             idx: 0,
+            tok_start: 0,
+            errors: Vec::new(),
+        }
+    }
+
+    /// A lexer that starts at the byte offset `offset` of `input`, which must
+    /// be the start of a token or whitespace preceding one
+    pub(crate) fn starting_at(input: &'a str, offset: usize) -> Self {
+        debug_assert!(input.is_char_boundary(offset), "{offset} is inside a char");
+        Self {
+            input,
+            chars: input[offset..].chars().peekable(),
+            idx: offset,
+            tok_start: offset,
+            errors: Vec::new(),
         }
     }
 
@@ -47,8 +234,8 @@ impl<'a> Lexer<'a> {
 
     fn advance_char(&mut self) -> Option<char> {
         let next = self.chars.next();
-        if let Some(_) = next {
-            self.idx += 1
+        if let Some(c) = next {
+            self.idx += c.len_utf8()
         }
 
         next
@@ -58,19 +245,50 @@ impl<'a> Lexer<'a> {
         self.chars.peek().copied()
     }
 
+    /// Checks whether the upcoming characters match `expected` without consuming them
+    fn lookahead(&self, expected: &str) -> bool {
+        let mut ahead = self.chars.clone();
+        expected.chars().all(|c| ahead.next() == Some(c))
+    }
+
+    /// Checks whether the char `n` places after the next one is `expected`
+    fn lookahead_nth(&self, n: usize, expected: char) -> bool {
+        self.chars.clone().nth(n) == Some(expected)
+    }
+
+    /// Produces a two char token of type `typ` if the char after the
+    /// current one is `next`, consuming it
+    fn try_compound(&mut self, next: char, typ: TokenType<'a>) -> Option<Token<'a>> {
+        if self.peek_char() != Some(next) {
+            return None;
+        }
+
+        let start = self.tok_start;
+        self.consume_char();
+        Some(Token {
+            typ,
+            span: Span::new(start, self.tok_id()),
+        })
+    }
+
+    /// Byte offset of the last byte of the most recently consumed char
     fn tok_id(&self) -> usize {
         self.idx - 1
     }
 
-    fn advance_token(&mut self) -> Token<'a> {
+    pub(crate) fn advance_token(&mut self) -> Token<'a> {
         loop {
+            self.tok_start = self.idx;
             let Some(next) = self.advance_char() else {
+                // points at the start of the last char so that the span can
+                // be sliced. Empty input has no last char to point at
+                let end = self.input[..self.idx]
+                    .char_indices()
+                    .next_back()
+                    .map_or(0, |(idx, _)| idx);
                 return Token {
                     typ: TokenType::EOF,
-                    span: Span {
-                        start: self.tok_id(),
-                        end: self.tok_id(),
-                    },
+                    span: Span { start: end, end },
                 };
             };
 
@@ -90,10 +308,14 @@ impl<'a> Lexer<'a> {
                 }
 
                 '{' => {
+                    if let Some('-') = self.peek_char() {
+                        return self.consume_block_comment();
+                    }
+
                     return Token {
                         typ: TokenType::LBrace,
                         span: Span::new(self.tok_id(), self.tok_id()),
-                    }
+                    };
                 }
 
                 '}' => {
@@ -103,11 +325,29 @@ impl<'a> Lexer<'a> {
                     }
                 }
 
+                '[' => {
+                    return Token {
+                        typ: TokenType::LBracket,
+                        span: Span::new(self.tok_id(), self.tok_id()),
+                    };
+                }
+
+                ']' => {
+                    return Token {
+                        typ: TokenType::RBracket,
+                        span: Span::new(self.tok_id(), self.tok_id()),
+                    };
+                }
+
                 ':' => {
+                    if let Some(token) = self.try_compound(':', TokenType::DoubleColon) {
+                        return token;
+                    }
+
                     return Token {
                         typ: TokenType::Colon,
                         span: Span::new(self.tok_id(), self.tok_id()),
-                    }
+                    };
                 }
 
                 ',' => {
@@ -118,10 +358,14 @@ impl<'a> Lexer<'a> {
                 }
 
                 '.' => {
+                    if let Some(token) = self.try_compound('.', TokenType::DoubleDot) {
+                        return token;
+                    }
+
                     return Token {
                         typ: TokenType::Dot,
                         span: Span::new(self.tok_id(), self.tok_id()),
-                    }
+                    };
                 }
 
                 '+' => {
@@ -136,10 +380,8 @@ impl<'a> Lexer<'a> {
                         return self.consume_comment();
                     }
 
-                    if let Some(t) = self.peek_char() {
-                        if t.is_digit(10) {
-                            return self.consume_number();
-                        }
+                    if let Some(token) = self.try_compound('>', TokenType::Arrow) {
+                        return token;
                     }
 
                     return Token {
@@ -164,9 +406,44 @@ impl<'a> Lexer<'a> {
 
                 '/' => {
                     return Token {
-                        typ: TokenType::Lparen,
+                        typ: TokenType::Slash,
                         span: Span::new(self.tok_id(), self.tok_id()),
-                    }
+                    };
+                }
+
+                ';' => {
+                    return Token {
+                        typ: TokenType::Semicolon,
+                        span: Span::new(self.tok_id(), self.tok_id()),
+                    };
+                }
+
+                '@' => {
+                    return Token {
+                        typ: TokenType::At,
+                        span: Span::new(self.tok_id(), self.tok_id()),
+                    };
+                }
+
+                '?' => {
+                    return Token {
+                        typ: TokenType::Question,
+                        span: Span::new(self.tok_id(), self.tok_id()),
+                    };
+                }
+
+                '#' => {
+                    return Token {
+                        typ: TokenType::Hash,
+                        span: Span::new(self.tok_id(), self.tok_id()),
+                    };
+                }
+
+                '^' => {
+                    return Token {
+                        typ: TokenType::Caret,
+                        span: Span::new(self.tok_id(), self.tok_id()),
+                    };
                 }
 
                 '\\' => {
@@ -177,6 +454,14 @@ impl<'a> Lexer<'a> {
                 }
 
                 '=' => {
+                    if let Some(token) = self.try_compound('=', TokenType::DoubleEqual) {
+                        return token;
+                    }
+
+                    if let Some(token) = self.try_compound('>', TokenType::FatArrow) {
+                        return token;
+                    }
+
                     return Token {
                         typ: TokenType::Equal,
                         span: Span::new(self.tok_id(), self.tok_id()),
@@ -184,6 +469,10 @@ impl<'a> Lexer<'a> {
                 }
 
                 '>' => {
+                    if let Some(token) = self.try_compound('=', TokenType::GreaterEqual) {
+                        return token;
+                    }
+
                     return Token {
                         typ: TokenType::RAngleBracket,
                         span: Span::new(self.tok_id(), self.tok_id()),
@@ -191,6 +480,10 @@ impl<'a> Lexer<'a> {
                 }
 
                 '<' => {
+                    if let Some(token) = self.try_compound('=', TokenType::LessEqual) {
+                        return token;
+                    }
+
                     return Token {
                         typ: TokenType::LAngleBracket,
                         span: Span::new(self.tok_id(), self.tok_id()),
@@ -198,6 +491,10 @@ impl<'a> Lexer<'a> {
                 }
 
                 '!' => {
+                    if let Some(token) = self.try_compound('=', TokenType::NotEqual) {
+                        return token;
+                    }
+
                     return Token {
                         typ: TokenType::Exclam,
                         span: Span::new(self.tok_id(), self.tok_id()),
@@ -205,6 +502,14 @@ impl<'a> Lexer<'a> {
                 }
 
                 '|' => {
+                    if let Some(token) = self.try_compound('|', TokenType::DoubleVbar) {
+                        return token;
+                    }
+
+                    if let Some(token) = self.try_compound('>', TokenType::Pipe) {
+                        return token;
+                    }
+
                     return Token {
                         typ: TokenType::Vbar,
                         span: Span::new(self.tok_id(), self.tok_id()),
@@ -212,15 +517,24 @@ impl<'a> Lexer<'a> {
                 }
 
                 '&' => {
+                    if let Some(token) = self.try_compound('&', TokenType::DoubleAmper) {
+                        return token;
+                    }
+
                     return Token {
                         typ: TokenType::Amper,
                         span: Span::new(self.tok_id(), self.tok_id()),
                     };
                 }
 
+                // Raw string literals
+                'r' if self.is_raw_string_start() => {
+                    return self.consume_raw_string();
+                }
+
                 // Identifiers
                 'a'..='z' | 'A'..='Z' | '_' => {
-                    let start = self.tok_id();
+                    let start = self.tok_start;
                     let mut len = 0;
 
                     while let Some(next) = self.peek_char() {
@@ -244,16 +558,18 @@ impl<'a> Lexer<'a> {
                     };
                 }
 
-                // NOTE: Missing support for full number parsing
-                //      numbers in bases other than decimal
-                //      exponential notation
-                '0'..='9' => {
-                    return self.consume_number();
+                c @ '0'..='9' => {
+                    return self.consume_number(c);
                 }
 
                 // String literals
                 '"' => {
-                    panic!("Unhandled: Tokenization of string literals")
+                    return self.consume_string();
+                }
+
+                // Char literals
+                '\'' => {
+                    return self.consume_char_literal();
                 }
 
                 // Skip Whitespace
@@ -261,177 +577,647 @@ impl<'a> Lexer<'a> {
                     continue;
                 }
 
-                _ => {
-                    return Token {
-                        typ: TokenType::EOF,
-                        span: Span { start: 0, end: 0 },
-                    }
+                c => {
+                    let start = self.tok_start;
+                    return self.error_token(LexErrorKind::UnexpectedChar(c), start);
                 }
             }
         }
     }
 
+    /// Consumes a line comment. Comments starting with `---` or `--|` are doc
+    /// comments, while longer runs of dashes like `-----` are plain comments
     fn consume_comment(&mut self) -> Token<'a> {
-        let start = self.tok_id();
+        let start = self.tok_start;
+        let is_doc = self.lookahead("-|") || (self.lookahead("--") && !self.lookahead("---"));
+
+        // the comment ends before the line break, be it `\n` or `\r\n`
         while let Some(c) = self.peek_char() {
-            if c != '\n' {
-                self.consume_char()
-            } else {
+            if c == '\n' || self.lookahead("\r\n") {
                 break;
             }
+            self.consume_char()
         }
 
-        return Token {
-            typ: TokenType::Comment(&self.input[start..self.tok_id() + 1]),
-            span: Span::new(start, self.tok_id()),
+        let typ = if is_doc {
+            // strip the `---` marker and the space that usually follows it
+            let doc = &self.input[start + 3..self.idx];
+            TokenType::DocComment(doc.strip_prefix(' ').unwrap_or(doc))
+        } else {
+            TokenType::Comment(&self.input[start..self.tok_id() + 1])
         };
+
+        Token {
+            typ,
+            span: Span::new(start, self.tok_id()),
+        }
     }
 
-    fn consume_number(&mut self) -> Token<'a> {
-        let start = self.tok_id();
-        let mut found_decimal_point = false;
+    /// Consumes a `{- ... -}` block comment. Block comments nest so that code
+    /// already containing block comments can be commented out
+    fn consume_block_comment(&mut self) -> Token<'a> {
+        let start = self.tok_start;
+        // the `-` of the opening delimiter
+        self.consume_char();
 
-        loop {
-            match self.peek_char() {
-                Some(t) if t.is_digit(10) => {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.advance_char() {
+                Some('{') if self.peek_char() == Some('-') => {
                     self.consume_char();
+                    depth += 1;
                 }
-
-                Some('.') => {
-                    if !found_decimal_point {
-                        found_decimal_point = true;
-                        self.consume_char();
-                    } else {
-                        break;
-                    }
+                Some('-') if self.peek_char() == Some('}') => {
+                    self.consume_char();
+                    depth -= 1;
+                }
+                Some(_) => {}
+                None => {
+                    // point at the opening delimiter that was never closed
+                    let kind = LexErrorKind::UnterminatedBlockComment;
+                    self.errors.push(LexError {
+                        kind,
+                        span: Span::new(start, start + 1),
+                    });
+                    return Token {
+                        typ: TokenType::Error(kind),
+                        span: Span::new(start, self.tok_id()),
+                    };
                 }
-
-                _ => break,
             }
         }
 
-        if found_decimal_point {
-            // parse float
-            let parsed_float =
-                (&self.input[start..=self.tok_id()])
-                    .parse::<f64>()
-                    .expect(&format!(
-                        "Error in parsing float: '{}'",
-                        &self.input[start..=self.tok_id()],
-                    ));
-            return Token {
-                typ: TokenType::FloatLit(parsed_float),
-                span: Span::new(start, self.tok_id()),
-            };
-        } else {
-            // parse int
-            let parsed_int = (&self.input[start..=self.tok_id()])
-                .parse::<i64>()
-                .expect(&format!(
-                    "Error in parsing int: {}",
-                    &self.input[start..=self.tok_id()],
-                ));
-            return Token {
-                typ: TokenType::IntegerLit(parsed_int),
-                span: Span::new(start, self.tok_id()),
-            };
+        Token {
+            typ: TokenType::Comment(&self.input[start..self.idx]),
+            span: Span::new(start, self.tok_id()),
         }
     }
-}
 
-fn is_valid_ident_char(c: char) -> bool {
-    c.is_alphanumeric() || c == '_'
-}
+    /// Consumes a string literal whose opening quote has already been consumed.
+    /// Handles both single line strings `"..."` and multi-line strings `"""..."""`
+    fn consume_string(&mut self) -> Token<'a> {
+        let start = self.tok_start;
 
-#[cfg(test)]
-mod tests {
-    use crate::{
-        lexer::Lexer,
-        span::Span,
-        token::{Token, TokenType},
-    };
+        if self.lookahead("\"\"") {
+            self.consume_char();
+            self.consume_char();
+            return self.consume_multiline_string(start);
+        }
 
-    use super::tokenize;
+        let mut value = String::new();
+        let mut error = None;
+        loop {
+            // single line strings may not contain a newline
+            if let Some('\n') | None = self.peek_char() {
+                return self.error_token(LexErrorKind::UnterminatedString, start);
+            }
 
-    #[test]
-    fn single_ident() {
-        let inp = "func";
-        let mut lexer = Lexer::new(inp);
+            match self.advance_char() {
+                Some('"') => break,
+                Some('\\') => match self.consume_escape() {
+                    Ok(c) => value.push(c),
+                    Err(e) => error = error.or(Some(e)),
+                },
+                Some(c) => value.push(c),
+                None => unreachable!(),
+            }
+        }
 
-        assert_eq!(
-            lexer.advance_token(),
-            Token {
-                typ: TokenType::Ident("func"),
-                span: Span::new(0, 3)
+        self.string_token(start, value, error)
+    }
+
+    fn consume_multiline_string(&mut self, start: usize) -> Token<'a> {
+        let mut value = String::new();
+        let mut error = None;
+        loop {
+            match self.advance_char() {
+                Some('"') if self.lookahead("\"\"") => {
+                    self.consume_char();
+                    self.consume_char();
+                    break;
+                }
+                Some('\\') => match self.consume_escape() {
+                    Ok(c) => value.push(c),
+                    Err(e) => error = error.or(Some(e)),
+                },
+                Some(c) => value.push(c),
+                None => return self.error_token(LexErrorKind::UnterminatedString, start),
             }
-        );
-        assert_eq!(
-            lexer.advance_token(),
-            Token {
-                typ: TokenType::EOF,
-                span: Span::new(3, 3)
+        }
+
+        self.string_token(start, value, error)
+    }
+
+    /// Produces the token for a string literal that started at `start` and
+    /// ends at the current char. Turns into an error token if any of the
+    /// escape sequences in it were invalid
+    fn string_token(&mut self, start: usize, value: String, error: Option<LexError>) -> Token<'a> {
+        let span = Span::new(start, self.tok_id());
+        match error {
+            None => Token {
+                typ: TokenType::StringLit(value),
+                span,
+            },
+            Some(error) => {
+                self.errors.push(error);
+                Token {
+                    typ: TokenType::Error(error.kind),
+                    span,
+                }
             }
-        );
+        }
     }
 
-    #[test]
-    fn space_separated_idents() {
-        let inp = "module Main";
-        let mut lexer = Lexer::new(inp);
+    /// Consumes a char literal whose opening quote has already been consumed.
+    ///
+    /// The closing quote must directly follow a single char or escape sequence,
+    /// so a quote that does not start a well formed literal never swallows the
+    /// code after it. This keeps the door open for primes in identifiers `x'`
+    fn consume_char_literal(&mut self) -> Token<'a> {
+        let start = self.tok_start;
 
-        assert_eq!(
-            lexer.advance_token(),
-            Token {
-                typ: TokenType::Module,
-                span: Span::new(0, 5)
+        let value = match self.peek_char() {
+            Some('\\') => {
+                self.consume_char();
+                self.consume_escape()
             }
-        );
-        assert_eq!(
-            lexer.advance_token(),
-            Token {
-                typ: TokenType::Ident("Main"),
-                span: Span::new(7, 10)
+            Some(c) if c != '\n' && c != '\'' && self.lookahead_nth(1, '\'') => {
+                self.consume_char();
+                Ok(c)
             }
-        );
-    }
+            // an empty literal `''`
+            Some('\'') => {
+                self.consume_char();
+                return self.error_token(LexErrorKind::InvalidCharLiteral, start);
+            }
+            _ => return self.error_token(LexErrorKind::UnexpectedChar('\''), start),
+        };
 
-    #[test]
-    fn keywords() {
-        let inp = "module type typealias exposing import";
-        let tokens = tokenize(inp).collect::<Vec<_>>();
-        assert_eq!(
-            tokens,
-            [
-                Token {
-                    typ: TokenType::Module,
-                    span: Span::new(0, 5)
-                },
-                Token {
-                    typ: TokenType::Type,
-                    span: Span::new(7, 10)
-                },
-                Token {
-                    typ: TokenType::TypeAlias,
-                    span: Span::new(12, 20)
-                },
-                Token {
-                    typ: TokenType::Exposing,
-                    span: Span::new(22, 29)
-                },
-                Token {
-                    typ: TokenType::Import,
-                    span: Span::new(31, 36)
-                },
+        if self.peek_char() != Some('\'') {
+            return self.error_token(LexErrorKind::InvalidCharLiteral, start);
+        }
+        self.consume_char();
+
+        match value {
+            Ok(c) => Token {
+                typ: TokenType::CharLit(c),
+                span: Span::new(start, self.tok_id()),
+            },
+            Err(error) => {
+                self.errors.push(error);
                 Token {
-                    typ: TokenType::EOF,
-                    span: Span::new(36, 36)
-                },
-            ]
-        );
+                    typ: TokenType::Error(error.kind),
+                    span: Span::new(start, self.tok_id()),
+                }
+            }
+        }
     }
 
-    #[test]
-    fn punctuation() {
+    /// Consumes the escape sequence following a backslash and returns the
+    /// character it represents
+    fn consume_escape(&mut self) -> Result<char, LexError> {
+        let start = self.tok_id();
+        let invalid = |lexer: &Self| LexError {
+            kind: LexErrorKind::InvalidEscape,
+            span: Span::new(start, lexer.tok_id()),
+        };
+
+        // A backslash at the end of a line or the input does not escape anything.
+        // Leave the newline for the string to report as unterminated
+        let Some(c) = self.peek_char().filter(|c| *c != '\n') else {
+            return Err(invalid(self));
+        };
+        self.consume_char();
+
+        match c {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '0' => Ok('\0'),
+            '"' => Ok('"'),
+            '\'' => Ok('\''),
+            '\\' => Ok('\\'),
+            'u' => self.consume_unicode_escape().ok_or_else(|| invalid(self)),
+            _ => Err(invalid(self)),
+        }
+    }
+
+    /// Consumes the `{XXXX}` part of a `\u{XXXX}` unicode escape
+    fn consume_unicode_escape(&mut self) -> Option<char> {
+        if self.peek_char() != Some('{') {
+            return None;
+        }
+        self.consume_char();
+
+        let mut code = String::new();
+        while let Some(c) = self.peek_char() {
+            if c.is_ascii_hexdigit() && code.len() < 6 {
+                code.push(c);
+                self.consume_char();
+            } else {
+                break;
+            }
+        }
+
+        if self.peek_char() != Some('}') {
+            return None;
+        }
+        self.consume_char();
+
+        u32::from_str_radix(&code, 16).ok().and_then(char::from_u32)
+    }
+
+    /// Checks whether an `r` that was just consumed starts a raw string
+    /// i.e. `r"..."` or `r#"..."#`
+    fn is_raw_string_start(&self) -> bool {
+        let mut ahead = self.chars.clone();
+        loop {
+            match ahead.next() {
+                Some('#') => continue,
+                Some('"') => return true,
+                _ => return false,
+            }
+        }
+    }
+
+    /// Consumes a raw string. Escape sequences are not processed and the string
+    /// may contain quotes if it is delimited with `#`s e.g. `r#"say "hi""#`
+    fn consume_raw_string(&mut self) -> Token<'a> {
+        let start = self.tok_start;
+
+        let mut hashes = 0;
+        while let Some('#') = self.peek_char() {
+            self.consume_char();
+            hashes += 1;
+        }
+        // opening quote
+        self.consume_char();
+
+        let closing = "#".repeat(hashes);
+        let mut value = String::new();
+        loop {
+            match self.advance_char() {
+                Some('"') if self.lookahead(&closing) => {
+                    for _ in 0..hashes {
+                        self.consume_char();
+                    }
+                    break;
+                }
+                Some(c) => value.push(c),
+                None => return self.error_token(LexErrorKind::UnterminatedString, start),
+            }
+        }
+
+        Token {
+            typ: TokenType::StringLit(value),
+            span: Span::new(start, self.tok_id()),
+        }
+    }
+
+    /// Records an error spanning from `start` to the current char and produces
+    /// the token standing in for the erroneous input
+    fn error_token(&mut self, kind: LexErrorKind, start: usize) -> Token<'a> {
+        let span = Span::new(start, self.tok_id());
+        self.errors.push(LexError { kind, span });
+        Token {
+            typ: TokenType::Error(kind),
+            span,
+        }
+    }
+
+    /// Consumes a numeric literal. `first` is its already consumed first digit.
+    /// A `-` before a number is a separate token, so that `n-1` is a subtraction.
+    ///
+    /// Handles `0x`, `0o` and `0b` prefixes, `_` separators, exponents and
+    /// type suffixes e.g. `0xFF_u8`, `1_000`, `6.02e23f64`
+    fn consume_number(&mut self, first_digit: char) -> Token<'a> {
+        let start = self.tok_start;
+        let mut literal = String::new();
+
+        let radix = match (first_digit, self.peek_char()) {
+            ('0', Some('x')) => 16,
+            ('0', Some('o')) => 8,
+            ('0', Some('b')) => 2,
+            _ => 10,
+        };
+
+        let mut is_float = false;
+        let mut missing_digits = false;
+        if radix == 10 {
+            literal.push(first_digit);
+            self.consume_digits(10, &mut literal);
+
+            // `..` following a number is a range rather than a decimal point
+            if self.peek_char() == Some('.') && !self.lookahead("..") {
+                is_float = true;
+                self.consume_char();
+                literal.push('.');
+                self.consume_digits(10, &mut literal);
+            }
+
+            if self.is_exponent_start() {
+                is_float = true;
+                self.consume_char();
+                literal.push('e');
+                if let Some(sign @ ('+' | '-')) = self.peek_char() {
+                    self.consume_char();
+                    literal.push(sign);
+                }
+                self.consume_digits(10, &mut literal);
+            }
+        } else {
+            // the base prefix
+            self.consume_char();
+            let len = literal.len();
+            self.consume_digits(radix, &mut literal);
+            missing_digits = literal.len() == len;
+        }
+
+        let mut suffix = String::new();
+        while let Some(c) = self.peek_char().filter(|c| is_valid_ident_char(*c)) {
+            self.consume_char();
+            suffix.push(c);
+        }
+
+        if missing_digits {
+            return self.error_token(LexErrorKind::InvalidNumber, start);
+        }
+
+        let suffix = match NumberSuffix::from_suffix(&suffix) {
+            _ if suffix.is_empty() => None,
+            Some(suffix) if !suffix.is_float() || radix == 10 => Some(suffix),
+            // digits that are not valid in the base of the literal e.g. `0b12`
+            None if suffix.starts_with(|c: char| c.is_ascii_digit()) => {
+                return self.error_token(LexErrorKind::InvalidNumber, start);
+            }
+            _ => return self.error_token(LexErrorKind::InvalidNumberSuffix, start),
+        };
+
+        // a literal with a fractional part or exponent can't have an integer type
+        if is_float && suffix.is_some_and(|suffix| !suffix.is_float()) {
+            return self.error_token(LexErrorKind::InvalidNumberSuffix, start);
+        }
+
+        if is_float || suffix.is_some_and(NumberSuffix::is_float) {
+            self.float_token(start, &literal, suffix)
+        } else {
+            self.integer_token(start, &literal, radix, suffix)
+        }
+    }
+
+    /// Consumes digits valid in `radix` and `_` separators, collecting the digits
+    fn consume_digits(&mut self, radix: u32, digits: &mut String) {
+        while let Some(c) = self.peek_char() {
+            if c.is_digit(radix) {
+                digits.push(c);
+            } else if c != '_' {
+                break;
+            }
+            self.consume_char();
+        }
+    }
+
+    /// Checks whether an exponent `e10`, `E-3`, `e+5` follows
+    fn is_exponent_start(&self) -> bool {
+        let mut ahead = self.chars.clone();
+        if !matches!(ahead.next(), Some('e' | 'E')) {
+            return false;
+        }
+
+        let mut next = ahead.next();
+        if let Some('+' | '-') = next {
+            next = ahead.next();
+        }
+
+        next.is_some_and(|c| c.is_ascii_digit())
+    }
+
+    fn integer_token(
+        &mut self,
+        start: usize,
+        literal: &str,
+        radix: u32,
+        suffix: Option<NumberSuffix>,
+    ) -> Token<'a> {
+        // only the parser knows whether the literal is negated, so the digits of
+        // the most negative value are accepted here and the parser checks the
+        // value it ends up with
+        let (min, max) = integer_range(suffix);
+        let value = i128::from_str_radix(literal, radix)
+            .ok()
+            .filter(|value| *value <= max.max(-min));
+
+        match value {
+            Some(value) => Token {
+                typ: TokenType::IntegerLit(value, suffix),
+                span: Span::new(start, self.tok_id()),
+            },
+            None => self.error_token(LexErrorKind::IntegerOverflow, start),
+        }
+    }
+
+    fn float_token(
+        &mut self,
+        start: usize,
+        literal: &str,
+        suffix: Option<NumberSuffix>,
+    ) -> Token<'a> {
+        let value = literal
+            .parse::<f64>()
+            .expect("decimal digits with a decimal point and exponent are a valid float");
+        let max = match suffix {
+            Some(NumberSuffix::F32) => f32::MAX as f64,
+            _ => f64::MAX,
+        };
+
+        if value.abs() > max {
+            return self.error_token(LexErrorKind::FloatOverflow, start);
+        }
+
+        Token {
+            typ: TokenType::FloatLit(value, suffix),
+            span: Span::new(start, self.tok_id()),
+        }
+    }
+}
+
+/// The inclusive range of an integer literal with `suffix`, which is that of
+/// an `i64` when it has none
+pub(crate) fn integer_range(suffix: Option<NumberSuffix>) -> (i128, i128) {
+    suffix
+        .and_then(NumberSuffix::int_range)
+        .unwrap_or((i64::MIN.into(), i64::MAX.into()))
+}
+
+fn is_valid_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        lexer::{LexError, LexErrorKind, Lexer},
+        span::Span,
+        token::{NumberSuffix, Token, TokenType},
+    };
+
+    use proptest::prelude::*;
+
+    use super::{tokenize, tokenize_lossless, tokenize_with_errors, Trivia, TriviaKind};
+
+    /// Concatenates the text of a lossless token stream
+    fn lossless_text(input: &str) -> String {
+        tokenize_lossless(input)
+            .iter()
+            .flat_map(|token| {
+                token
+                    .leading_trivia
+                    .iter()
+                    .map(|trivia| trivia.text)
+                    .chain(std::iter::once(token.text))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn single_ident() {
+        let inp = "func";
+        let mut lexer = Lexer::new(inp);
+
+        assert_eq!(
+            lexer.advance_token(),
+            Token {
+                typ: TokenType::Ident("func"),
+                span: Span::new(0, 3)
+            }
+        );
+        assert_eq!(
+            lexer.advance_token(),
+            Token {
+                typ: TokenType::EOF,
+                span: Span::new(3, 3)
+            }
+        );
+    }
+
+    #[test]
+    fn space_separated_idents() {
+        let inp = "module Main";
+        let mut lexer = Lexer::new(inp);
+
+        assert_eq!(
+            lexer.advance_token(),
+            Token {
+                typ: TokenType::Module,
+                span: Span::new(0, 5)
+            }
+        );
+        assert_eq!(
+            lexer.advance_token(),
+            Token {
+                typ: TokenType::Ident("Main"),
+                span: Span::new(7, 10)
+            }
+        );
+    }
+
+    #[test]
+    fn keywords() {
+        let inp = "module type typealias exposing import union record struct let in if then else match case of as where true false";
+        let tokens = tokenize(inp).collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            [
+                Token {
+                    typ: TokenType::Module,
+                    span: Span::new(0, 5)
+                },
+                Token {
+                    typ: TokenType::Type,
+                    span: Span::new(7, 10)
+                },
+                Token {
+                    typ: TokenType::TypeAlias,
+                    span: Span::new(12, 20)
+                },
+                Token {
+                    typ: TokenType::Exposing,
+                    span: Span::new(22, 29)
+                },
+                Token {
+                    typ: TokenType::Import,
+                    span: Span::new(31, 36)
+                },
+                Token {
+                    typ: TokenType::Union,
+                    span: Span::new(38, 42)
+                },
+                Token {
+                    typ: TokenType::Record,
+                    span: Span::new(44, 49)
+                },
+                Token {
+                    typ: TokenType::Struct,
+                    span: Span::new(51, 56)
+                },
+                Token {
+                    typ: TokenType::Let,
+                    span: Span::new(58, 60)
+                },
+                Token {
+                    typ: TokenType::In,
+                    span: Span::new(62, 63)
+                },
+                Token {
+                    typ: TokenType::If,
+                    span: Span::new(65, 66)
+                },
+                Token {
+                    typ: TokenType::Then,
+                    span: Span::new(68, 71)
+                },
+                Token {
+                    typ: TokenType::Else,
+                    span: Span::new(73, 76)
+                },
+                Token {
+                    typ: TokenType::Match,
+                    span: Span::new(78, 82)
+                },
+                Token {
+                    typ: TokenType::Case,
+                    span: Span::new(84, 87)
+                },
+                Token {
+                    typ: TokenType::Of,
+                    span: Span::new(89, 90)
+                },
+                Token {
+                    typ: TokenType::As,
+                    span: Span::new(92, 93)
+                },
+                Token {
+                    typ: TokenType::Where,
+                    span: Span::new(95, 99)
+                },
+                Token {
+                    typ: TokenType::True,
+                    span: Span::new(101, 104)
+                },
+                Token {
+                    typ: TokenType::False,
+                    span: Span::new(106, 110)
+                },
+                Token {
+                    typ: TokenType::EOF,
+                    span: Span::new(110, 110)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn punctuation() {
         let inp = "<>!{}wow%&";
         let tokens = tokenize(inp).collect::<Vec<_>>();
         assert_eq!(
@@ -479,10 +1265,16 @@ mod tests {
         let tokens = tokenize(inp).collect::<Vec<_>>();
         assert_eq!(
             tokens[0..tokens.len() - 1],
-            [Token {
-                typ: TokenType::IntegerLit(-23),
-                span: Span { start: 0, end: 2 }
-            }]
+            [
+                Token {
+                    typ: TokenType::Minus,
+                    span: Span { start: 0, end: 0 }
+                },
+                Token {
+                    typ: TokenType::IntegerLit(23, None),
+                    span: Span { start: 1, end: 2 }
+                }
+            ]
         );
     }
 
@@ -493,7 +1285,7 @@ mod tests {
         assert_eq!(
             tokens[0..tokens.len() - 1],
             [Token {
-                typ: TokenType::IntegerLit(23),
+                typ: TokenType::IntegerLit(23, None),
                 span: Span { start: 0, end: 1 }
             }]
         );
@@ -505,10 +1297,16 @@ mod tests {
         let tokens = tokenize(inp).collect::<Vec<_>>();
         assert_eq!(
             tokens[0..tokens.len() - 1],
-            [Token {
-                typ: TokenType::FloatLit(-23.9),
-                span: Span { start: 0, end: 4 }
-            }]
+            [
+                Token {
+                    typ: TokenType::Minus,
+                    span: Span { start: 0, end: 0 }
+                },
+                Token {
+                    typ: TokenType::FloatLit(23.9, None),
+                    span: Span { start: 1, end: 4 }
+                }
+            ]
         );
     }
 
@@ -519,7 +1317,7 @@ mod tests {
         assert_eq!(
             tokens[0..tokens.len() - 1],
             [Token {
-                typ: TokenType::FloatLit(23.3),
+                typ: TokenType::FloatLit(23.3, None),
                 span: Span { start: 0, end: 3 }
             }]
         );
@@ -577,12 +1375,8 @@ mod tests {
                     span: Span { start: 21, end: 21 },
                 },
                 Token {
-                    typ: TokenType::Equal,
-                    span: Span { start: 23, end: 23 },
-                },
-                Token {
-                    typ: TokenType::RAngleBracket,
-                    span: Span { start: 24, end: 24 },
+                    typ: TokenType::FatArrow,
+                    span: Span { start: 23, end: 24 },
                 },
                 Token {
                     typ: TokenType::LBrace,
@@ -650,12 +1444,8 @@ parse = (x: String) => { }"#;
                     span: Span { start: 51, end: 51 }
                 },
                 Token {
-                    typ: TokenType::Equal,
-                    span: Span { start: 53, end: 53 }
-                },
-                Token {
-                    typ: TokenType::RAngleBracket,
-                    span: Span { start: 54, end: 54 }
+                    typ: TokenType::FatArrow,
+                    span: Span { start: 53, end: 54 },
                 },
                 Token {
                     typ: TokenType::LBrace,
@@ -668,4 +1458,857 @@ parse = (x: String) => { }"#;
             ]
         );
     }
+
+    #[test]
+    fn string_literal() {
+        let inp = r#"greeting = "Hujambo""#;
+        let tokens = tokenize(inp).collect::<Vec<_>>();
+        assert_eq!(
+            tokens[0..tokens.len() - 1],
+            [
+                Token {
+                    typ: TokenType::Ident("greeting"),
+                    span: Span { start: 0, end: 7 }
+                },
+                Token {
+                    typ: TokenType::Equal,
+                    span: Span { start: 9, end: 9 }
+                },
+                Token {
+                    typ: TokenType::StringLit("Hujambo".to_string()),
+                    span: Span { start: 11, end: 19 }
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn string_escapes() {
+        let inp = r#""tab\tnew\nquote\"slash\\smile\u{1F600}""#;
+        let tokens = tokenize(inp).collect::<Vec<_>>();
+        assert_eq!(
+            tokens[0..tokens.len() - 1],
+            [Token {
+                typ: TokenType::StringLit("tab\tnew\nquote\"slash\\smile\u{1F600}".to_string()),
+                span: Span { start: 0, end: 39 }
+            }]
+        );
+    }
+
+    #[test]
+    fn raw_string() {
+        let inp = r##"r"C:\path" r#"say "hi""#"##;
+        let tokens = tokenize(inp).collect::<Vec<_>>();
+        assert_eq!(
+            tokens[0..tokens.len() - 1],
+            [
+                Token {
+                    typ: TokenType::StringLit(r"C:\path".to_string()),
+                    span: Span { start: 0, end: 9 }
+                },
+                Token {
+                    typ: TokenType::StringLit(r#"say "hi""#.to_string()),
+                    span: Span { start: 11, end: 23 }
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn raw_prefix_ident() {
+        let inp = "radius";
+        let tokens = tokenize(inp).collect::<Vec<_>>();
+        assert_eq!(
+            tokens[0..tokens.len() - 1],
+            [Token {
+                typ: TokenType::Ident("radius"),
+                span: Span { start: 0, end: 5 }
+            }]
+        );
+    }
+
+    #[test]
+    fn multiline_string() {
+        let inp = "\"\"\"first\n  \"second\"\\t\"\"\"";
+        let tokens = tokenize(inp).collect::<Vec<_>>();
+        assert_eq!(
+            tokens[0..tokens.len() - 1],
+            [Token {
+                typ: TokenType::StringLit("first\n  \"second\"\t".to_string()),
+                span: Span { start: 0, end: 23 }
+            }]
+        );
+    }
+
+    #[test]
+    fn empty_strings() {
+        let inp = "\"\" \"\"\"\"\"\"";
+        let tokens = tokenize(inp).collect::<Vec<_>>();
+        assert_eq!(
+            tokens[0..tokens.len() - 1],
+            [
+                Token {
+                    typ: TokenType::StringLit(String::new()),
+                    span: Span { start: 0, end: 1 }
+                },
+                Token {
+                    typ: TokenType::StringLit(String::new()),
+                    span: Span { start: 3, end: 8 }
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn hello_example() {
+        let inp = include_str!("../../../examples/hello.meri");
+        let tokens = tokenize(inp).collect::<Vec<_>>();
+        assert!(tokens.contains(&Token {
+            typ: TokenType::StringLit("Hello World".to_string()),
+            span: Span { start: 64, end: 76 }
+        }));
+    }
+
+    #[test]
+    fn empty_input() {
+        let tokens = tokenize("").collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            [Token {
+                typ: TokenType::EOF,
+                span: Span::new(0, 0)
+            }]
+        );
+    }
+
+    #[test]
+    fn unexpected_chars() {
+        let inp = "a ~ b $";
+        let (tokens, errors) = tokenize_with_errors(inp);
+        assert_eq!(
+            tokens,
+            [
+                Token {
+                    typ: TokenType::Ident("a"),
+                    span: Span::new(0, 0)
+                },
+                Token {
+                    typ: TokenType::Error(LexErrorKind::UnexpectedChar('~')),
+                    span: Span::new(2, 2)
+                },
+                Token {
+                    typ: TokenType::Ident("b"),
+                    span: Span::new(4, 4)
+                },
+                Token {
+                    typ: TokenType::Error(LexErrorKind::UnexpectedChar('$')),
+                    span: Span::new(6, 6)
+                },
+                Token {
+                    typ: TokenType::EOF,
+                    span: Span::new(6, 6)
+                },
+            ]
+        );
+        assert_eq!(
+            errors,
+            [
+                LexError {
+                    kind: LexErrorKind::UnexpectedChar('~'),
+                    span: Span::new(2, 2)
+                },
+                LexError {
+                    kind: LexErrorKind::UnexpectedChar('$'),
+                    span: Span::new(6, 6)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_string() {
+        let inp = "\"abc\nnext";
+        let (tokens, errors) = tokenize_with_errors(inp);
+        assert_eq!(
+            tokens[0..tokens.len() - 1],
+            [
+                Token {
+                    typ: TokenType::Error(LexErrorKind::UnterminatedString),
+                    span: Span::new(0, 3)
+                },
+                Token {
+                    typ: TokenType::Ident("next"),
+                    span: Span::new(5, 8)
+                },
+            ]
+        );
+        assert_eq!(
+            errors,
+            [LexError {
+                kind: LexErrorKind::UnterminatedString,
+                span: Span::new(0, 3)
+            }]
+        );
+    }
+
+    #[test]
+    fn unterminated_multiline_string() {
+        let inp = "\"\"\"abc\n";
+        let (_, errors) = tokenize_with_errors(inp);
+        assert_eq!(
+            errors,
+            [LexError {
+                kind: LexErrorKind::UnterminatedString,
+                span: Span::new(0, 6)
+            }]
+        );
+    }
+
+    #[test]
+    fn invalid_escapes() {
+        let inp = r#""a\qb" "\u{110000}" ok"#;
+        let (tokens, errors) = tokenize_with_errors(inp);
+        assert_eq!(
+            tokens[0..tokens.len() - 1],
+            [
+                Token {
+                    typ: TokenType::Error(LexErrorKind::InvalidEscape),
+                    span: Span::new(0, 5)
+                },
+                Token {
+                    typ: TokenType::Error(LexErrorKind::InvalidEscape),
+                    span: Span::new(7, 18)
+                },
+                Token {
+                    typ: TokenType::Ident("ok"),
+                    span: Span::new(20, 21)
+                },
+            ]
+        );
+        assert_eq!(
+            errors,
+            [
+                LexError {
+                    kind: LexErrorKind::InvalidEscape,
+                    span: Span::new(2, 3)
+                },
+                LexError {
+                    kind: LexErrorKind::InvalidEscape,
+                    span: Span::new(8, 17)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn integer_overflow() {
+        let inp = "99999999999999999999 1";
+        let (tokens, errors) = tokenize_with_errors(inp);
+        assert_eq!(
+            tokens[0..tokens.len() - 1],
+            [
+                Token {
+                    typ: TokenType::Error(LexErrorKind::IntegerOverflow),
+                    span: Span::new(0, 19)
+                },
+                Token {
+                    typ: TokenType::IntegerLit(1, None),
+                    span: Span::new(21, 21)
+                },
+            ]
+        );
+        assert_eq!(
+            errors,
+            [LexError {
+                kind: LexErrorKind::IntegerOverflow,
+                span: Span::new(0, 19)
+            }]
+        );
+    }
+
+    #[test]
+    fn compound_operators() {
+        let inp = "=> -> == != <= >= && || :: |> ^ .. / = - < > ! | & : .";
+        let tokens = tokenize(inp).map(|t| t.typ).collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            [
+                TokenType::FatArrow,
+                TokenType::Arrow,
+                TokenType::DoubleEqual,
+                TokenType::NotEqual,
+                TokenType::LessEqual,
+                TokenType::GreaterEqual,
+                TokenType::DoubleAmper,
+                TokenType::DoubleVbar,
+                TokenType::DoubleColon,
+                TokenType::Pipe,
+                TokenType::Caret,
+                TokenType::DoubleDot,
+                TokenType::Slash,
+                TokenType::Equal,
+                TokenType::Minus,
+                TokenType::LAngleBracket,
+                TokenType::RAngleBracket,
+                TokenType::Exclam,
+                TokenType::Vbar,
+                TokenType::Amper,
+                TokenType::Colon,
+                TokenType::Dot,
+                TokenType::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn maximal_munch() {
+        let inp = "Option::Some(..)|>x^2";
+        let tokens = tokenize(inp).collect::<Vec<_>>();
+        assert_eq!(
+            tokens[0..tokens.len() - 1],
+            [
+                Token {
+                    typ: TokenType::Ident("Option"),
+                    span: Span { start: 0, end: 5 }
+                },
+                Token {
+                    typ: TokenType::DoubleColon,
+                    span: Span { start: 6, end: 7 }
+                },
+                Token {
+                    typ: TokenType::Ident("Some"),
+                    span: Span { start: 8, end: 11 }
+                },
+                Token {
+                    typ: TokenType::Lparen,
+                    span: Span { start: 12, end: 12 }
+                },
+                Token {
+                    typ: TokenType::DoubleDot,
+                    span: Span { start: 13, end: 14 }
+                },
+                Token {
+                    typ: TokenType::RParen,
+                    span: Span { start: 15, end: 15 }
+                },
+                Token {
+                    typ: TokenType::Pipe,
+                    span: Span { start: 16, end: 17 }
+                },
+                Token {
+                    typ: TokenType::Ident("x"),
+                    span: Span { start: 18, end: 18 }
+                },
+                Token {
+                    typ: TokenType::Caret,
+                    span: Span { start: 19, end: 19 }
+                },
+                Token {
+                    typ: TokenType::IntegerLit(2, None),
+                    span: Span { start: 20, end: 20 }
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn int_range() {
+        let inp = "1..10";
+        let tokens = tokenize(inp).collect::<Vec<_>>();
+        assert_eq!(
+            tokens[0..tokens.len() - 1],
+            [
+                Token {
+                    typ: TokenType::IntegerLit(1, None),
+                    span: Span { start: 0, end: 0 }
+                },
+                Token {
+                    typ: TokenType::DoubleDot,
+                    span: Span { start: 1, end: 2 }
+                },
+                Token {
+                    typ: TokenType::IntegerLit(10, None),
+                    span: Span { start: 3, end: 4 }
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn brackets_and_punctuation() {
+        let inp = "[x@y];?#z";
+        let tokens = tokenize(inp).collect::<Vec<_>>();
+        assert_eq!(
+            tokens[0..tokens.len() - 1],
+            [
+                Token {
+                    typ: TokenType::LBracket,
+                    span: Span { start: 0, end: 0 }
+                },
+                Token {
+                    typ: TokenType::Ident("x"),
+                    span: Span { start: 1, end: 1 }
+                },
+                Token {
+                    typ: TokenType::At,
+                    span: Span { start: 2, end: 2 }
+                },
+                Token {
+                    typ: TokenType::Ident("y"),
+                    span: Span { start: 3, end: 3 }
+                },
+                Token {
+                    typ: TokenType::RBracket,
+                    span: Span { start: 4, end: 4 }
+                },
+                Token {
+                    typ: TokenType::Semicolon,
+                    span: Span { start: 5, end: 5 }
+                },
+                Token {
+                    typ: TokenType::Question,
+                    span: Span { start: 6, end: 6 }
+                },
+                Token {
+                    typ: TokenType::Hash,
+                    span: Span { start: 7, end: 7 }
+                },
+                Token {
+                    typ: TokenType::Ident("z"),
+                    span: Span { start: 8, end: 8 }
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn number_bases() {
+        let inp = "0xFF 0o755 0b1010 -0x_1f";
+        let tokens = tokenize(inp).map(|t| t.typ).collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            [
+                TokenType::IntegerLit(255, None),
+                TokenType::IntegerLit(493, None),
+                TokenType::IntegerLit(10, None),
+                TokenType::Minus,
+                TokenType::IntegerLit(31, None),
+                TokenType::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn number_separators_and_exponents() {
+        let inp = "1_000_000 6.02e23 1e-9 2.5E+3 -1_0.5";
+        let tokens = tokenize(inp).map(|t| t.typ).collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            [
+                TokenType::IntegerLit(1_000_000, None),
+                TokenType::FloatLit(6.02e23, None),
+                TokenType::FloatLit(1e-9, None),
+                TokenType::FloatLit(2.5e3, None),
+                TokenType::Minus,
+                TokenType::FloatLit(10.5, None),
+                TokenType::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn number_suffixes() {
+        let inp = "10i32 255u8 3.0f32 1f64 0xFFu16";
+        let tokens = tokenize(inp).collect::<Vec<_>>();
+        assert_eq!(
+            tokens[0..tokens.len() - 1],
+            [
+                Token {
+                    typ: TokenType::IntegerLit(10, Some(NumberSuffix::I32)),
+                    span: Span::new(0, 4)
+                },
+                Token {
+                    typ: TokenType::IntegerLit(255, Some(NumberSuffix::U8)),
+                    span: Span::new(6, 10)
+                },
+                Token {
+                    typ: TokenType::FloatLit(3.0, Some(NumberSuffix::F32)),
+                    span: Span::new(12, 17)
+                },
+                Token {
+                    typ: TokenType::FloatLit(1.0, Some(NumberSuffix::F64)),
+                    span: Span::new(19, 22)
+                },
+                Token {
+                    typ: TokenType::IntegerLit(0xFF, Some(NumberSuffix::U16)),
+                    span: Span::new(24, 30)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_numbers() {
+        let inp = "256u8 129i8 0x 0b12 10i7 1e999 1.5i32";
+        let (_, errors) = tokenize_with_errors(inp);
+        assert_eq!(
+            errors,
+            [
+                LexError {
+                    kind: LexErrorKind::IntegerOverflow,
+                    span: Span::new(0, 4)
+                },
+                LexError {
+                    kind: LexErrorKind::IntegerOverflow,
+                    span: Span::new(6, 10)
+                },
+                LexError {
+                    kind: LexErrorKind::InvalidNumber,
+                    span: Span::new(12, 13)
+                },
+                LexError {
+                    kind: LexErrorKind::InvalidNumber,
+                    span: Span::new(15, 18)
+                },
+                LexError {
+                    kind: LexErrorKind::InvalidNumberSuffix,
+                    span: Span::new(20, 23)
+                },
+                LexError {
+                    kind: LexErrorKind::FloatOverflow,
+                    span: Span::new(25, 29)
+                },
+                LexError {
+                    kind: LexErrorKind::InvalidNumberSuffix,
+                    span: Span::new(31, 36)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn integer_type_bounds() {
+        let inp = "18446744073709551615u64 0xFFFF_FFFF_FFFF_FFFFu64 -128i8 \
+                   9223372036854775807 -9223372036854775808";
+        let tokens = tokenize(inp).map(|t| t.typ).collect::<Vec<_>>();
+        assert_eq!(
+            tokens[0..tokens.len() - 1],
+            [
+                TokenType::IntegerLit(u64::MAX.into(), Some(NumberSuffix::U64)),
+                TokenType::IntegerLit(u64::MAX.into(), Some(NumberSuffix::U64)),
+                TokenType::Minus,
+                TokenType::IntegerLit(128, Some(NumberSuffix::I8)),
+                TokenType::IntegerLit(i64::MAX.into(), None),
+                TokenType::Minus,
+                TokenType::IntegerLit(i128::from(i64::MAX) + 1, None),
+            ]
+        );
+
+        let inp = "18446744073709551616u64 -129i8 -32769i16 -9223372036854775809";
+        let (_, errors) = tokenize_with_errors(inp);
+        assert_eq!(
+            errors.iter().map(|e| e.kind).collect::<Vec<_>>(),
+            [LexErrorKind::IntegerOverflow; 4]
+        );
+    }
+
+    #[test]
+    fn char_literals() {
+        let inp = r"'a' '\n' '\'' '\u{1F600}' 'ж'";
+        let tokens = tokenize(inp).collect::<Vec<_>>();
+        assert_eq!(
+            tokens[0..tokens.len() - 1],
+            [
+                Token {
+                    typ: TokenType::CharLit('a'),
+                    span: Span::new(0, 2)
+                },
+                Token {
+                    typ: TokenType::CharLit('\n'),
+                    span: Span::new(4, 7)
+                },
+                Token {
+                    typ: TokenType::CharLit('\''),
+                    span: Span::new(9, 12)
+                },
+                Token {
+                    typ: TokenType::CharLit('\u{1F600}'),
+                    span: Span::new(14, 24)
+                },
+                Token {
+                    typ: TokenType::CharLit('ж'),
+                    span: Span::new(26, 29)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_char_literals() {
+        let inp = r"'' '\q' 'ab'";
+        let (tokens, errors) = tokenize_with_errors(inp);
+        assert_eq!(
+            tokens.into_iter().map(|t| t.typ).collect::<Vec<_>>(),
+            [
+                TokenType::Error(LexErrorKind::InvalidCharLiteral),
+                TokenType::Error(LexErrorKind::InvalidEscape),
+                TokenType::Error(LexErrorKind::UnexpectedChar('\'')),
+                TokenType::Ident("ab"),
+                TokenType::Error(LexErrorKind::UnexpectedChar('\'')),
+                TokenType::EOF,
+            ]
+        );
+        assert_eq!(
+            errors[1],
+            LexError {
+                kind: LexErrorKind::InvalidEscape,
+                span: Span::new(4, 5)
+            }
+        );
+    }
+
+    #[test]
+    fn apostrophe_after_ident() {
+        // a stray quote must not start a char literal that swallows `+ y`
+        let inp = "x' + y";
+        let tokens = tokenize(inp).map(|t| t.typ).collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            [
+                TokenType::Ident("x"),
+                TokenType::Error(LexErrorKind::UnexpectedChar('\'')),
+                TokenType::Plus,
+                TokenType::Ident("y"),
+                TokenType::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn non_ascii_source() {
+        let inp = "-- Habari za asubuhi ☀️\nsalamu = \"Hujambo 👋\" ¿";
+        let (tokens, errors) = tokenize_with_errors(inp);
+        let comment_end = inp.find('\n').unwrap() - 1;
+        let ident_start = comment_end + 2;
+        assert_eq!(
+            tokens,
+            [
+                Token {
+                    typ: TokenType::Comment("-- Habari za asubuhi ☀️"),
+                    span: Span::new(0, comment_end)
+                },
+                Token {
+                    typ: TokenType::Ident("salamu"),
+                    span: Span::new(ident_start, ident_start + 5)
+                },
+                Token {
+                    typ: TokenType::Equal,
+                    span: Span::new(ident_start + 7, ident_start + 7)
+                },
+                Token {
+                    typ: TokenType::StringLit("Hujambo 👋".to_string()),
+                    span: Span::new(ident_start + 9, ident_start + 9 + 13)
+                },
+                Token {
+                    typ: TokenType::Error(LexErrorKind::UnexpectedChar('¿')),
+                    span: Span::new(ident_start + 24, ident_start + 25)
+                },
+                Token {
+                    typ: TokenType::EOF,
+                    span: Span::new(ident_start + 24, ident_start + 24)
+                },
+            ]
+        );
+        assert!(inp.is_char_boundary(tokens[5].span.start));
+        assert_eq!(
+            &inp[tokens[3].span.start..=tokens[3].span.end],
+            "\"Hujambo 👋\""
+        );
+        assert_eq!(&inp[errors[0].span.start..=errors[0].span.end], "¿");
+    }
+
+    #[test]
+    fn doc_comments() {
+        let inp = "--- Adds two numbers\n--| together\n---\n-----\n-- plain";
+        let tokens = tokenize(inp).collect::<Vec<_>>();
+        assert_eq!(
+            tokens[0..tokens.len() - 1],
+            [
+                Token {
+                    typ: TokenType::DocComment("Adds two numbers"),
+                    span: Span::new(0, 19)
+                },
+                Token {
+                    typ: TokenType::DocComment("together"),
+                    span: Span::new(21, 32)
+                },
+                Token {
+                    typ: TokenType::DocComment(""),
+                    span: Span::new(34, 36)
+                },
+                Token {
+                    typ: TokenType::Comment("-----"),
+                    span: Span::new(38, 42)
+                },
+                Token {
+                    typ: TokenType::Comment("-- plain"),
+                    span: Span::new(44, 51)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn block_comments() {
+        let inp = "a {- outer {- inner -} still outer -} b {--}";
+        let tokens = tokenize(inp).collect::<Vec<_>>();
+        assert_eq!(
+            tokens[0..tokens.len() - 1],
+            [
+                Token {
+                    typ: TokenType::Ident("a"),
+                    span: Span::new(0, 0)
+                },
+                Token {
+                    typ: TokenType::Comment("{- outer {- inner -} still outer -}"),
+                    span: Span::new(2, 36)
+                },
+                Token {
+                    typ: TokenType::Ident("b"),
+                    span: Span::new(38, 38)
+                },
+                Token {
+                    typ: TokenType::Comment("{--}"),
+                    span: Span::new(40, 43)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_block_comment() {
+        let inp = "x {- {- -}\ny";
+        let (tokens, errors) = tokenize_with_errors(inp);
+        assert_eq!(
+            tokens[0..tokens.len() - 1],
+            [
+                Token {
+                    typ: TokenType::Ident("x"),
+                    span: Span::new(0, 0)
+                },
+                Token {
+                    typ: TokenType::Error(LexErrorKind::UnterminatedBlockComment),
+                    span: Span::new(2, 11)
+                },
+            ]
+        );
+        assert_eq!(
+            errors,
+            [LexError {
+                kind: LexErrorKind::UnterminatedBlockComment,
+                span: Span::new(2, 3)
+            }]
+        );
+    }
+
+    #[test]
+    fn keyword_prefixed_idents() {
+        let inp = "iffy letter types matches inner";
+        let tokens = tokenize(inp).map(|t| t.typ).collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            [
+                TokenType::Ident("iffy"),
+                TokenType::Ident("letter"),
+                TokenType::Ident("types"),
+                TokenType::Ident("matches"),
+                TokenType::Ident("inner"),
+                TokenType::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn lossless_trivia() {
+        let inp = "x  -- note\r\n\t{- c -} = 1\n";
+        let tokens = tokenize_lossless(inp);
+        assert_eq!(tokens.len(), 4);
+
+        assert_eq!(tokens[0].leading_trivia, []);
+        assert_eq!(tokens[0].text, "x");
+        assert_eq!(
+            tokens[1].leading_trivia,
+            [
+                Trivia {
+                    kind: TriviaKind::Whitespace,
+                    text: "  ",
+                    span: Span::new(1, 2)
+                },
+                Trivia {
+                    kind: TriviaKind::Comment,
+                    text: "-- note",
+                    span: Span::new(3, 9)
+                },
+                Trivia {
+                    kind: TriviaKind::Newline,
+                    text: "\r\n",
+                    span: Span::new(10, 11)
+                },
+                Trivia {
+                    kind: TriviaKind::Whitespace,
+                    text: "\t",
+                    span: Span::new(12, 12)
+                },
+                Trivia {
+                    kind: TriviaKind::Comment,
+                    text: "{- c -}",
+                    span: Span::new(13, 19)
+                },
+                Trivia {
+                    kind: TriviaKind::Whitespace,
+                    text: " ",
+                    span: Span::new(20, 20)
+                },
+            ]
+        );
+        assert_eq!(tokens[1].token.typ, TokenType::Equal);
+        assert_eq!(tokens[2].text, "1");
+        assert_eq!(tokens[3].token.typ, TokenType::EOF);
+        assert_eq!(
+            tokens[3].leading_trivia,
+            [Trivia {
+                kind: TriviaKind::Newline,
+                text: "\n",
+                span: Span::new(24, 24)
+            }]
+        );
+        assert_eq!(lossless_text(inp), inp);
+    }
+
+    #[test]
+    fn lossless_examples() {
+        for inp in [
+            include_str!("../../../examples/data_definitions.meri"),
+            include_str!("../../../examples/definitions.meri"),
+            include_str!("../../../examples/functions.meri"),
+            include_str!("../../../examples/hello.meri"),
+            include_str!("../../../examples/module.meri"),
+        ] {
+            assert_eq!(lossless_text(inp), inp);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn lossless_roundtrip_any_input(inp in any::<String>()) {
+            prop_assert_eq!(lossless_text(&inp), inp);
+        }
+
+        #[test]
+        fn lossless_roundtrip_meri_like_input(
+            inp in r#"([a-z_]{1,6}|[0-9][0-9a-fxob_.e]{0,5}|"[^"\n]{0,5}"?|'.{0,2}'?|--[^\n]{0,5}|\{-|-\}|[ \t\r\n]{1,3}|[(){}\[\]<>=:.,+*/%!|&^@?#;\\-]|[é😀ж])*"#
+        ) {
+            prop_assert_eq!(lossless_text(&inp), inp);
+        }
+    }
 }
@@ -1,156 +1,546 @@
 #![allow(unused)]
 
-use meri_ast::{Definition, Expression, FunctionSignature, Ident, Pattern, TypePath};
+use meri_ast::{
+    Clause, Definition, Exposing, FunctionSignature, Ident, Literal, Module, ModuleHeader,
+    ModuleType, Pattern, TypeExpr,
+};
 use nom::{
     branch::alt,
     bytes::complete::tag,
     combinator::{map, opt},
-    error::{ErrorKind, ParseError},
-    multi::separated_list1,
-    sequence::delimited,
+    multi::{many0, separated_list0, separated_list1},
+    sequence::{delimited, pair, preceded, terminated},
     IResult, Parser,
 };
 use nom_supreme::ParserExt;
 
 use crate::{
+    clauses::group_clauses,
+    data::parse_type_definition,
+    error::{record, recording_expected, Expectations, TokenError},
+    expr::{consumed_span, parse_block, parse_expr},
+    layout::{with_layout, LayoutToken},
+    lexer::{tokenize_with_errors, LexError},
+    module::parse_module_header,
+    pattern::parse_pattern,
+    recovery::{collect_errors, skip_definition},
     span::Span,
     token::{Token, TokenType},
+    types::{bind_type_params, parse_generic_params, parse_type},
 };
 
-mod lexer;
-mod span;
-mod token;
+mod clauses;
+mod data;
+mod error;
+mod expr;
+pub mod incremental;
+pub mod layout;
+pub mod lexer;
+mod module;
+mod pattern;
+mod recovery;
+pub mod span;
+pub mod token;
+mod types;
+
+pub use error::{Expected, ParseError, ParseErrorKind};
+
+type ParseResult<'a, 'b, O> = IResult<&'a [Token<'b>], O, TokenError<'a, 'b>>;
+
+/// Parses a whole module, from its header to the end of the input.
+/// A module named `Main` is an executable with its `main` function as the
+/// entrypoint.
+///
+/// Parsing recovers from errors, so the module is always returned along with
+/// every error found, ordered by their position. The parts of the module that
+/// failed to parse are left as `Error` placeholders
+pub fn parse_module(input: &str) -> (Module<'_>, Vec<ParseError>) {
+    let (tokens, lex_errors) = significant_tokens(input);
+    let mut errors = Vec::new();
+
+    let (parsed, expected) = recording_expected(|| parse_module_header(&tokens));
+    let (rest, header) = match parsed {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            // a broken header is skipped from where it failed while a missing
+            // one leaves the definitions to be parsed
+            let rest = match tokens.first().map(|t| &t.typ) {
+                Some(TokenType::Module) => skip_definition(failed_at(&error, &tokens)),
+                _ => &tokens[..],
+            };
+            errors.push(syntax_error(error, &expected));
+            let header = ModuleHeader {
+                name: String::new(),
+                exposing: Exposing::Items(Vec::new()),
+                imports: Vec::new(),
+            };
+            (rest, header)
+        }
+    };
+
+    let (items, syntax_errors) = parse_definitions(rest);
+    let (definitions, clause_errors) = group_clauses(items);
+    errors.extend(syntax_errors);
+    errors.extend(clause_errors);
+    collect_errors(&tokens, &definitions, &mut errors);
+
+    let entrypoint = definitions.iter().find_map(|definition| match definition {
+        Definition::FunctionDefinition { ident, .. } if ident.0 == "main" => Some(*ident),
+        _ => None,
+    });
+    let typ = match entrypoint {
+        _ if header.name != "Main" => ModuleType::LibraryModule,
+        Some(entrypoint) => ModuleType::ExecutableModule { entrypoint },
+        None => {
+            errors.push(ParseError {
+                kind: ParseErrorKind::MissingEntrypoint,
+                span: consumed_span(&tokens, rest),
+                context: Vec::new(),
+            });
+            ModuleType::LibraryModule
+        }
+    };
+
+    // the parser fails at any token the lexer could not make sense of, which
+    // is already reported by the lexer with the span of the offending text
+    errors.retain(|error| {
+        !matches!(error.kind, ParseErrorKind::Unexpected { .. })
+            || !tokens
+                .iter()
+                .any(|token| matches!(token.typ, TokenType::Error(_)) && token.span == error.span)
+    });
+    errors.extend(lex_errors.into_iter().map(|error| ParseError {
+        kind: ParseErrorKind::Lex(error.kind),
+        span: error.span,
+        context: Vec::new(),
+    }));
+    errors.sort_by_key(|error| error.span.start);
+
+    let module = Module {
+        header,
+        typ,
+        definitions,
+    };
+
+    (module, errors)
+}
+
+/// A syntax error at the token where `error` occurred, along with what was
+/// `expected` there by the parsers that failed without it
+fn syntax_error(error: nom::Err<TokenError<'_, '_>>, expected: &Expectations) -> ParseError {
+    match error {
+        nom::Err::Error(error) | nom::Err::Failure(error) => {
+            error.with_recorded(expected).into_parse_error()
+        }
+        nom::Err::Incomplete(_) => unreachable!("the token parsers are all complete"),
+    }
+}
+
+/// The tokens from the one at which parsing `input` failed with `error`
+fn failed_at<'a, 'b>(
+    error: &nom::Err<TokenError<'a, 'b>>,
+    input: &'a [Token<'b>],
+) -> &'a [Token<'b>] {
+    match error {
+        nom::Err::Error(error) | nom::Err::Failure(error) => error.input,
+        nom::Err::Incomplete(_) => input,
+    }
+}
+
+/// The tokens of `input` that are meaningful to the parser, i.e. without
+/// comments, along with the errors found lexing it.
+///
+/// A definition starts at the beginning of a line, so the `Newline` that the
+/// layout pass inserts before every token in the first column outside of any
+/// brackets is kept. This ends a body such as `fib(0) = 0` that is not
+/// delimited by braces.
+///
+/// Doc comments are kept, following the `Newline`, only where they directly
+/// precede a definition. Anywhere else, such as inside a body or before the
+/// module header, they are dropped like any other comment
+fn significant_tokens(input: &str) -> (Vec<Token<'_>>, Vec<LexError>) {
+    let (lexed, errors) = tokenize_with_errors(input);
+    let mut tokens = Vec::new();
+    let mut docs = Vec::new();
+
+    for LayoutToken { token, layout } in with_layout(input, lexed, true) {
+        match token.typ {
+            TokenType::DocComment(_) => docs.push(token),
+            TokenType::Newline if layout.column == 0 => tokens.push(token),
+            TokenType::Newline | TokenType::Comment(_) | TokenType::Indent | TokenType::Dedent => {}
+            _ => {
+                let at_line_start = tokens
+                    .last()
+                    .is_none_or(|last: &Token| last.typ == TokenType::Newline);
+                let starts_definition = at_line_start
+                    && !matches!(
+                        token.typ,
+                        TokenType::Module | TokenType::Import | TokenType::EOF
+                    );
+                if starts_definition {
+                    tokens.append(&mut docs);
+                } else {
+                    docs.clear();
+                }
+                tokens.push(token);
+            }
+        }
+    }
+
+    (tokens, errors)
+}
+
+/// Any number of `Newline` tokens separating definitions
+fn newlines<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, ()> {
+    map(many0(token_type(TokenType::Newline)), |_| ())(input)
+}
+
+/// All the definitions up to the end of the input along with their spans.
+/// The clauses of a function are still separate definitions here.
+///
+/// A definition that fails to parse, or is followed by more tokens on its
+/// line, is reported and skipped from where it failed up to the start of the
+/// next definition, leaving a `Definition::Error` in its place
+fn parse_definitions<'b>(
+    mut input: &[Token<'b>],
+) -> (Vec<(Definition<'b>, Span)>, Vec<ParseError>) {
+    let mut definitions = Vec::new();
+    let mut errors = Vec::new();
+
+    loop {
+        while let Some((
+            Token {
+                typ: TokenType::Newline,
+                ..
+            },
+            rest,
+        )) = input.split_first()
+        {
+            input = rest;
+        }
+        if matches!(input.first().map(|t| &t.typ), None | Some(TokenType::EOF)) {
+            break;
+        }
+
+        // the tokens from the start of the broken definition and from where it failed
+        let (parsed, expected) = recording_expected(|| parse_definition(input));
+        let (broken, failed_at) = match parsed {
+            Ok((rest, definition)) => {
+                definitions.push((definition, consumed_span(input, rest)));
+                input = rest;
+                match rest.first() {
+                    Some(Token {
+                        typ: TokenType::Newline | TokenType::EOF,
+                        ..
+                    })
+                    | None => continue,
+                    Some(_) => errors.push(
+                        TokenError::expected(rest, Vec::new())
+                            .with_recorded(&expected)
+                            .into_parse_error(),
+                    ),
+                }
+                (rest, rest)
+            }
+            Err(error) => {
+                let failed_at = failed_at(&error, input);
+                errors.push(syntax_error(error, &expected));
+                (input, failed_at)
+            }
+        };
 
+        input = skip_definition(failed_at);
+        let span = consumed_span(broken, input);
+        definitions.push((Definition::Error { span }, span));
+    }
+
+    (definitions, errors)
+}
+
+fn parse_definition<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Definition<'b>> {
+    alt((
+        map(parse_type_definition, Definition::TypeDefinition),
+        parse_function_definition,
+    ))(input)
+}
+
+/// A function or value definition, starting with its signature. The body is
+/// optional, as is the `=` before a body in braces
+/// ```meri
+/// square: x => Int {
+///   x ^ 2
+/// }
+/// ```
+/// A definition without a signature is a single clause binding the arguments
+/// with patterns
+/// ```meri
+/// fib(0) = 0
+/// ```
 fn parse_function_definition<'a, 'b>(
     input: &'a [Token<'b>],
-) -> IResult<&'a [Token<'b>], Definition<'b>, nom::error::Error<&'a [Token<'b>]>> {
+) -> ParseResult<'a, 'b, Definition<'b>> {
+    let (input, doc) = parse_doc_comments(input)?;
+    let start = input;
     let (input, ident) = ident(input)?;
-    let (input, sig) = parse_function_signature(input)?;
-    let (input, _) = token_type(TokenType::Equal)(input)?;
-    let (input, body) = delimited(
-        token_type(TokenType::LBrace),
-        parse_expr,
-        token_type(TokenType::RBrace),
-    )(input)?;
-
-    Ok((input, Definition::FunctionDefinition { ident, sig, body }))
+
+    if token_type(TokenType::Colon)(input).is_err() {
+        let (input, (params, body)) = pair(
+            parse_clause_params,
+            preceded(token_type(TokenType::Equal), parse_expr),
+        )
+        .context(format!("clause of `{}`", ident.0))
+        .parse(input)?;
+        let clause = Clause {
+            params,
+            body,
+            span: consumed_span(start, input),
+        };
+
+        return Ok((
+            input,
+            Definition::FunctionDefinition {
+                doc,
+                ident,
+                sig: None,
+                clauses: vec![clause],
+            },
+        ));
+    }
+
+    let (input, sig) = parse_function_signature
+        .context(format!("function signature of `{}`", ident.0))
+        .parse(input)?;
+    let body = |input: &'a [Token<'b>]| match input.first().map(|t| &t.typ) {
+        Some(TokenType::Equal) => preceded(token_type(TokenType::Equal), parse_expr)(input),
+        _ => parse_block(input),
+    };
+    let (input, body) = match input.first().map(|t| &t.typ) {
+        Some(TokenType::Equal | TokenType::LBrace) => {
+            map(body.context(format!("body of `{}`", ident.0)), Some)(input)?
+        }
+        _ => {
+            record(input, || {
+                Expected::tokens(&[TokenType::Equal, TokenType::LBrace])
+            });
+            (input, None)
+        }
+    };
+
+    let clauses = body
+        .map(|body| Clause {
+            params: sig
+                .params
+                .iter()
+                .map(|(pattern, _)| pattern.clone())
+                .collect(),
+            body,
+            span: consumed_span(start, input),
+        })
+        .into_iter()
+        .collect();
+
+    Ok((
+        input,
+        Definition::FunctionDefinition {
+            doc,
+            ident,
+            sig: Some(sig),
+            clauses,
+        },
+    ))
 }
 
-// TODO: actually parse expressions
-fn parse_expr<'a, 'b>(
-    input: &'a [Token<'b>],
-) -> IResult<&'a [Token<'b>], Expression, nom::error::Error<&'a [Token<'b>]>> {
-    map(ident, |_| Expression::Unit)(input)
+/// The patterns of a clause `(pattern, ...)`. A clause without parentheses has
+/// no parameters
+fn parse_clause_params<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Vec<Pattern<'b>>> {
+    let (input, params) = opt(delimited(
+        token_type(TokenType::Lparen),
+        comma_separated(parse_pattern),
+        token_type(TokenType::RParen),
+    ))(input)?;
+
+    Ok((input, params.unwrap_or_default()))
+}
+
+/// Collects the doc comments preceding a definition into a single text
+fn parse_doc_comments<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Option<String>> {
+    let doc_comment = |input: &'a [Token<'b>]| match input.split_first() {
+        Some((
+            Token {
+                typ: TokenType::DocComment(doc),
+                ..
+            },
+            rest,
+        )) => Ok((rest, *doc)),
+        _ => Err(nom::Err::Error(TokenError::expected(input, Vec::new()))),
+    };
+
+    map(many0(doc_comment), |lines| {
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    })(input)
 }
 
 fn parse_single_parameter<'a, 'b>(
     input: &'a [Token<'b>],
-) -> IResult<&'a [Token<'b>], (Pattern<'b>, Option<TypePath<'b>>), nom::error::Error<&'a [Token<'b>]>>
-{
+) -> ParseResult<'a, 'b, (Pattern<'b>, Option<TypeExpr<'b>>)> {
     let (input, pattern) = parse_pattern(input)?;
     let Ok((input, _)) = token_type(TokenType::Colon)(input) else {
         return Ok((input, (pattern, None)));
     };
-    let (input, typ_path) = parse_type_path(input)?;
+    let (input, typ) = parse_type(input)?;
 
-    Ok((input, (pattern, Some(typ_path))))
+    Ok((input, (pattern, Some(typ))))
 }
 
 fn parse_function_params<'a, 'b>(
     input: &'a [Token<'b>],
-) -> IResult<
-    &'a [Token<'b>],
-    Vec<(Pattern<'b>, Option<TypePath<'b>>)>,
-    nom::error::Error<&'a [Token<'b>]>,
-> {
+) -> ParseResult<'a, 'b, Vec<(Pattern<'b>, Option<TypeExpr<'b>>)>> {
     delimited(
         token_type(TokenType::Lparen),
         separated_list1(token_type(TokenType::Comma), parse_single_parameter),
-        token_type(TokenType::RParen),
+        list_end(TokenType::RParen),
     )(input)
 }
 
 fn parse_function_signature<'a, 'b>(
     input: &'a [Token<'b>],
-) -> IResult<&'a [Token<'b>], FunctionSignature<'b>, nom::error::Error<&'a [Token<'b>]>> {
+) -> ParseResult<'a, 'b, FunctionSignature<'b>> {
     let (input, _) = token_type(TokenType::Colon)(input)?;
+    let (input, type_params) = parse_generic_params(input)?;
 
+    // the parentheses around a single parameter are optional
     let with_params = |input| {
-        let (input, func_params) = parse_function_params(input)?;
-        let (input, _) = token_type(TokenType::Equal)(input)?;
-        let (input, _) = token_type(TokenType::RAngleBracket)(input)?;
-        let (input, return_type) = parse_type_path(input)?;
+        let (input, func_params) = alt((
+            parse_function_params,
+            map(parse_single_parameter, |param| vec![param]),
+        ))(input)?;
+        let (input, _) = token_type(TokenType::FatArrow)(input)?;
+        let (input, return_type) = parse_type(input)?;
 
-        Ok((
-            input,
-            FunctionSignature {
-                params: func_params,
-                return_type,
-            },
-        ))
+        Ok((input, (func_params, return_type)))
     };
 
     let without_params = |input| {
-        let (input, return_type) = parse_type_path(input)?;
+        let (input, return_type) = parse_type(input)?;
 
-        Ok((
-            input,
-            FunctionSignature {
-                params: Vec::new(),
-                return_type,
-            },
-        ))
+        Ok((input, (Vec::new(), return_type)))
     };
 
-    alt((with_params, without_params))(input)
-}
+    let (input, (mut params, mut return_type)) = alt((with_params, without_params))(input)?;
 
-// TODO: only parses as identifiers currently. Add other items like generics etc.
-fn parse_type_path<'a, 'b>(
-    input: &'a [Token<'b>],
-) -> IResult<&'a [Token<'b>], TypePath<'b>, nom::error::Error<&'a [Token<'b>]>> {
-    map(ident, |ident| TypePath { ident })(input)
+    for (_, typ) in &mut params {
+        if let Some(typ) = typ {
+            bind_type_params(typ, &type_params);
+        }
+    }
+    bind_type_params(&mut return_type, &type_params);
+
+    Ok((
+        input,
+        FunctionSignature {
+            type_params,
+            params,
+            return_type,
+        },
+    ))
 }
 
-/// A  pattern used in match statements and in binding fucntion arguments
-// TODO: currently only handles binding to a name and not destructuring
-//      add destructuring and biding with more complex pattern matching
-fn parse_pattern<'a, 'b>(
-    input: &'a [Token<'b>],
-) -> IResult<&'a [Token<'b>], Pattern<'b>, nom::error::Error<&'a [Token<'b>]>> {
-    map(ident, |b| Pattern::Binding(b))(input)
+/// Items separated by commas allowing a trailing comma
+fn comma_separated<'a, 'b: 'a, O>(
+    item: impl FnMut(&'a [Token<'b>]) -> ParseResult<'a, 'b, O>,
+) -> impl FnMut(&'a [Token<'b>]) -> ParseResult<'a, 'b, Vec<O>> {
+    terminated(
+        separated_list0(token_type(TokenType::Comma), item),
+        opt(token_type(TokenType::Comma)),
+    )
 }
 
 fn token_type<'a, 'b: 'a>(
     tok_typ: TokenType<'_>,
-) -> impl Fn(
-    &'a [Token<'b>],
-) -> IResult<&'a [Token<'b>], Token<'b>, nom::error::Error<&'a [Token<'b>]>>
-       + '_ {
-    return move |input| match input.split_first() {
-        None => Err(nom::Err::Error(nom::error::Error::from_error_kind(
+) -> impl Fn(&'a [Token<'b>]) -> ParseResult<'a, 'b, Token<'b>> + '_ {
+    move |input| match input.split_first() {
+        Some((t @ Token { typ, .. }, rest)) if *typ == tok_typ => Ok((rest, t.clone())),
+
+        _ => Err(nom::Err::Error(TokenError::expected(
             input,
-            nom::error::ErrorKind::Eof,
+            vec![Expected::token(&tok_typ)],
         ))),
+    }
+}
 
-        Some((t @ Token { typ, .. }, rest)) if *typ == tok_typ => Ok((rest, *t)),
+/// Items separated by commas up to and including the token closing the list.
+/// Unlike `separated_list0`, an item that fails to parse is reported when it
+/// got further than the missing closing token rather than ending the list
+fn comma_list<'a, 'b: 'a, O>(
+    item: impl Parser<&'a [Token<'b>], O, TokenError<'a, 'b>>,
+    close: TokenType<'static>,
+) -> impl FnMut(&'a [Token<'b>]) -> ParseResult<'a, 'b, Vec<O>> {
+    closed_list(item, close, false)
+}
 
-        Some((t, rest)) => Err(nom::Err::Error(nom::error::Error::from_error_kind(
-            input,
-            ErrorKind::Tag,
-        ))),
-    };
+/// A `comma_list` that may also have a comma after its last item
+fn trailing_comma_list<'a, 'b: 'a, O>(
+    item: impl Parser<&'a [Token<'b>], O, TokenError<'a, 'b>>,
+    close: TokenType<'static>,
+) -> impl FnMut(&'a [Token<'b>]) -> ParseResult<'a, 'b, Vec<O>> {
+    closed_list(item, close, true)
 }
 
-fn ident<'a, 'b>(
-    input: &'a [Token<'b>],
-) -> IResult<&'a [Token<'b>], Ident<'b>, nom::error::Error<&'a [Token<'b>]>> {
-    match input.split_first() {
-        None => Err(nom::Err::Error(nom::error::Error::from_error_kind(
+fn closed_list<'a, 'b: 'a, O>(
+    mut item: impl Parser<&'a [Token<'b>], O, TokenError<'a, 'b>>,
+    close: TokenType<'static>,
+    trailing_comma: bool,
+) -> impl FnMut(&'a [Token<'b>]) -> ParseResult<'a, 'b, Vec<O>> {
+    move |mut input| {
+        let mut items = Vec::new();
+        let item_error = loop {
+            match item.parse(input) {
+                Ok((rest, parsed)) => {
+                    items.push(parsed);
+                    input = rest;
+                }
+                Err(nom::Err::Error(error)) if items.is_empty() || trailing_comma => {
+                    break Some(error)
+                }
+                Err(error) => return Err(error),
+            }
+            match token_type(TokenType::Comma)(input) {
+                Ok((rest, _)) => input = rest,
+                Err(_) => break None,
+            }
+        };
+
+        match list_end(close.clone())(input) {
+            Ok((rest, _)) => Ok((rest, items)),
+            Err(nom::Err::Error(error)) => Err(nom::Err::Error(match item_error {
+                Some(item_error) => nom::error::ParseError::or(error, item_error),
+                None => error,
+            })),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+/// The token closing a list of items separated by commas. Where it is
+/// missing, another comma would also have been accepted
+fn list_end<'a, 'b: 'a>(
+    tok_typ: TokenType<'_>,
+) -> impl Fn(&'a [Token<'b>]) -> ParseResult<'a, 'b, Token<'b>> + '_ {
+    move |input| match input.split_first() {
+        Some((t @ Token { typ, .. }, rest)) if *typ == tok_typ => Ok((rest, t.clone())),
+
+        _ => Err(nom::Err::Error(TokenError::expected(
             input,
-            nom::error::ErrorKind::Eof,
+            vec![
+                Expected::token(&tok_typ),
+                Expected::token(&TokenType::Comma),
+            ],
         ))),
+    }
+}
 
+fn ident<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Ident<'b>> {
+    match input.split_first() {
         Some((
             Token {
                 typ: TokenType::Ident(ident),
@@ -159,16 +549,47 @@ fn ident<'a, 'b>(
             rest,
         )) => Ok((rest, Ident(ident))),
 
-        Some((t, rest)) => Err(nom::Err::Error(nom::error::Error::from_error_kind(
+        _ => Err(nom::Err::Error(TokenError::expected(
             input,
-            ErrorKind::Tag,
+            vec![Expected::Identifier],
         ))),
     }
 }
 
+fn literal<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Literal> {
+    let not_literal = || nom::Err::Error(TokenError::expected(input, vec![Expected::Literal]));
+    let Some((token, rest)) = input.split_first() else {
+        return Err(not_literal());
+    };
+
+    let literal = match &token.typ {
+        TokenType::IntegerLit(int, suffix) => Literal::Int(*int, *suffix),
+        TokenType::FloatLit(float, suffix) => Literal::Float(*float, *suffix),
+        TokenType::StringLit(string) => Literal::String(string.clone()),
+        TokenType::CharLit(c) => Literal::Char(*c),
+        TokenType::True => Literal::Bool(true),
+        TokenType::False => Literal::Bool(false),
+        _ => return Err(not_literal()),
+    };
+
+    Ok((rest, literal))
+}
+
+/// A number literal with its sign flipped, for a literal following a `-`
+fn negated(literal: &Literal) -> Option<Literal> {
+    match *literal {
+        Literal::Int(int, suffix) => Some(Literal::Int(-int, suffix)),
+        Literal::Float(float, suffix) => Some(Literal::Float(-float, suffix)),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::lexer::tokenize;
+    use cool_asserts::assert_matches;
+    use meri_ast::Expression;
+
+    use crate::lexer::{tokenize, LexErrorKind};
 
     use super::*;
 
@@ -185,19 +606,30 @@ mod tests {
                     span: Span::new(35, 35)
                 }][..],
                 Definition::FunctionDefinition {
+                    doc: None,
                     ident: Ident("id"),
-                    sig: FunctionSignature {
+                    sig: Some(FunctionSignature {
+                        type_params: vec![],
                         params: vec![(
                             Pattern::Binding(Ident("x")),
-                            Some(TypePath {
-                                ident: Ident("Number")
+                            Some(TypeExpr::Named {
+                                path: vec![Ident("Number")],
+                                args: vec![]
                             })
                         )],
-                        return_type: TypePath {
-                            ident: Ident("Number")
+                        return_type: TypeExpr::Named {
+                            path: vec![Ident("Number")],
+                            args: vec![]
                         }
-                    },
-                    body: Expression::Unit
+                    }),
+                    clauses: vec![Clause {
+                        params: vec![Pattern::Binding(Ident("x"))],
+                        body: Expression::Variable {
+                            path: vec![Ident("x")],
+                            span: Span::new(33, 33)
+                        },
+                        span: Span::new(0, 35)
+                    }]
                 }
             ))
         )
@@ -232,8 +664,9 @@ mod tests {
                 }][..],
                 vec![(
                     Pattern::Binding(Ident("x")),
-                    Some(TypePath {
-                        ident: Ident("String")
+                    Some(TypeExpr::Named {
+                        path: vec![Ident("String")],
+                        args: vec![]
                     })
                 )]
             ))
@@ -274,20 +707,23 @@ mod tests {
                 vec![
                     (
                         Pattern::Binding(Ident("x")),
-                        Some(TypePath {
-                            ident: Ident("Int")
+                        Some(TypeExpr::Named {
+                            path: vec![Ident("Int")],
+                            args: vec![]
                         })
                     ),
                     (
                         Pattern::Binding(Ident("y")),
-                        Some(TypePath {
-                            ident: Ident("Int")
+                        Some(TypeExpr::Named {
+                            path: vec![Ident("Int")],
+                            args: vec![]
                         })
                     ),
                     (
                         Pattern::Binding(Ident("z")),
-                        Some(TypePath {
-                            ident: Ident("Int")
+                        Some(TypeExpr::Named {
+                            path: vec![Ident("Int")],
+                            args: vec![]
                         })
                     )
                 ],
@@ -295,6 +731,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn func_destructuring_params() {
+        let tokens: Vec<_> = tokenize("({ r, g, b }: Color, (x, _))").collect();
+        let (_, params) = parse_function_params(&tokens).unwrap();
+        assert_matches!(
+            &params[..],
+            [
+                (Pattern::Record { fields, rest: false }, Some(TypeExpr::Named { .. })),
+                (Pattern::Tuple(elements), None),
+            ] => {
+                assert_eq!(fields.len(), 3);
+                assert_eq!(elements[1], Pattern::Wildcard);
+            }
+        );
+    }
+
     #[test]
     fn func_sig_constant() {
         let tokens: Vec<_> = tokenize(": String").collect();
@@ -302,9 +754,11 @@ mod tests {
         assert_eq!(
             signature,
             FunctionSignature {
+                type_params: vec![],
                 params: vec![],
-                return_type: TypePath {
-                    ident: Ident("String")
+                return_type: TypeExpr::Named {
+                    path: vec![Ident("String")],
+                    args: vec![]
                 }
             }
         );
@@ -336,9 +790,11 @@ mod tests {
                     },
                 ][..],
                 FunctionSignature {
+                    type_params: vec![],
                     params: vec![(Pattern::Binding(Ident("x",)), None)],
-                    return_type: TypePath {
-                        ident: Ident("String",),
+                    return_type: TypeExpr::Named {
+                        path: vec![Ident("String")],
+                        args: vec![]
                     },
                 },
             ),)
@@ -371,17 +827,555 @@ mod tests {
                     },
                 ][..],
                 FunctionSignature {
+                    type_params: vec![],
                     params: vec![(
                         Pattern::Binding(Ident("x",)),
-                        Some(TypePath {
-                            ident: Ident("String")
+                        Some(TypeExpr::Named {
+                            path: vec![Ident("String")],
+                            args: vec![]
                         })
                     )],
-                    return_type: TypePath {
-                        ident: Ident("String",),
+                    return_type: TypeExpr::Named {
+                        path: vec![Ident("String")],
+                        args: vec![]
                     },
                 },
             ),)
         );
     }
+
+    #[test]
+    fn func_signature_split_arrow() {
+        // `(x)` on its own is a valid type, but `= >` must not be read as `=>`
+        let tokens: Vec<_> = tokenize(":(x) = > String").collect();
+        let (rest, signature) = parse_function_signature(&tokens).unwrap();
+        assert_eq!(rest[0].typ, TokenType::Equal);
+        assert_eq!(signature.return_type, TypeExpr::Variable(Ident("x")));
+    }
+
+    #[test]
+    fn char_literal_body() {
+        let tokens: Vec<_> = tokenize(r"newline : Char = { '\n' }").collect();
+        let (_, definition) = parse_function_definition(&tokens).unwrap();
+        assert_eq!(
+            definition,
+            Definition::FunctionDefinition {
+                doc: None,
+                ident: Ident("newline"),
+                sig: Some(FunctionSignature {
+                    type_params: vec![],
+                    params: vec![],
+                    return_type: TypeExpr::Named {
+                        path: vec![Ident("Char")],
+                        args: vec![]
+                    }
+                }),
+                clauses: vec![Clause {
+                    params: vec![],
+                    body: Expression::Literal {
+                        value: Literal::Char('\n'),
+                        span: Span::new(19, 22)
+                    },
+                    span: Span::new(0, 24)
+                }]
+            }
+        );
+    }
+
+    #[test]
+    fn func_def_with_docs() {
+        let tokens: Vec<_> =
+            tokenize("--- The number one\n--- Always `1`\none : Int = { 1 }").collect();
+        let (_, definition) = parse_function_definition(&tokens).unwrap();
+        assert_eq!(definition.doc(), Some("The number one\nAlways `1`"));
+    }
+
+    #[test]
+    fn func_def_block_body() {
+        let tokens: Vec<_> = tokenize("area : (w, h) => Int = { let a = w * h; a }").collect();
+        let (_, definition) = parse_function_definition(&tokens).unwrap();
+        let Definition::FunctionDefinition { mut clauses, .. } = definition else {
+            panic!("expected a function definition");
+        };
+        assert_matches!(clauses.remove(0).body, Expression::Block { statements, .. } => {
+            assert_eq!(statements.len(), 1);
+        });
+    }
+
+    #[test]
+    fn executable_module() {
+        let module = parse_valid(
+            "module Main exposing (main)\n\
+             import Stdio\n\
+             \n\
+             -- entrypoint\n\
+             main : Unit = { Stdio.print \"Hello\" }",
+        );
+        assert_eq!(module.header.name, "Main");
+        assert_eq!(module.header.imports.len(), 1);
+        assert_eq!(
+            module.typ,
+            ModuleType::ExecutableModule {
+                entrypoint: Ident("main")
+            }
+        );
+        assert_eq!(module.definitions.len(), 1);
+    }
+
+    #[test]
+    fn missing_entrypoint() {
+        let (module, errors) =
+            parse_module("module Main exposing (start)\nstart : Unit = { print \"Hello\" }");
+        assert_eq!(module.typ, ModuleType::LibraryModule);
+        assert_eq!(
+            errors,
+            [ParseError {
+                kind: ParseErrorKind::MissingEntrypoint,
+                span: Span::new(0, 27),
+                context: Vec::new()
+            }]
+        );
+    }
+
+    #[test]
+    fn library_module() {
+        let module = parse_valid(
+            "module Shapes exposing (..)\n\
+             struct Rect = { size: Int }\n\
+             area : (r: Rect) => Int = { r.size ^ 2 }",
+        );
+        assert_eq!(module.typ, ModuleType::LibraryModule);
+        assert_eq!(module.definitions.len(), 2);
+    }
+
+    #[test]
+    fn module_error_span() {
+        let (module, errors) = parse_module("module Main exposing (main)\nmain : Int = { 1 }\n)");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].span, Span::new(47, 47));
+        assert_eq!(
+            errors[0].to_string(),
+            "expected one of `record`, `struct`, `type`, `union`, identifier, found `)`"
+        );
+        assert_matches!(
+            &module.definitions[..],
+            [
+                Definition::FunctionDefinition { .. },
+                Definition::Error {
+                    span: Span { start: 47, end: 47 }
+                }
+            ]
+        );
+
+        let (module, errors) = parse_module("main : Int = { 1 }");
+        assert_eq!(
+            errors,
+            [ParseError {
+                kind: ParseErrorKind::Unexpected {
+                    found: "`main`".to_string(),
+                    expected: vec![Expected::Token("module".to_string())]
+                },
+                span: Span::new(0, 3),
+                context: vec![]
+            }]
+        );
+        assert_eq!(module.header.name, "");
+        assert_eq!(function_names(&module.definitions), ["main"]);
+    }
+
+    #[test]
+    fn recovers_at_definitions() {
+        let (module, errors) = parse_module(
+            "module Main exposing (main)\n\
+             one : Int = ) 1\n\
+             two : Int = 2\n\
+             three : Int = 3 3 )\n\
+             type = {}\n\
+             main : Int = { two }",
+        );
+        let spans = errors.iter().map(|error| error.span).collect::<Vec<_>>();
+        assert_eq!(
+            spans,
+            [Span::new(40, 40), Span::new(76, 76), Span::new(83, 83)]
+        );
+        let kinds = module
+            .definitions
+            .iter()
+            .map(|definition| match definition {
+                Definition::FunctionDefinition { ident, .. } => ident.0,
+                Definition::TypeDefinition(_) => "type",
+                Definition::Error { .. } => "error",
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, ["error", "two", "three", "error", "error", "main"]);
+    }
+
+    #[test]
+    fn recovers_after_doc_comments() {
+        let source = "--- The module\n\
+                      module M exposing (..)\n\
+                      --- Broken\n\
+                      --- over two lines\n\
+                      one : Int = )\n\
+                      two : Int = 2";
+        let (module, errors) = parse_module(source);
+        let doc = source.find("--- Broken").unwrap();
+        let paren = source.rfind(')').unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].span, Span::new(paren, paren));
+        assert_matches!(
+            &module.definitions[..],
+            [
+                Definition::Error { span },
+                Definition::FunctionDefinition { .. }
+            ] => assert_eq!(*span, Span::new(doc, paren))
+        );
+    }
+
+    #[test]
+    fn recovers_in_blocks() {
+        let (module, errors) = parse_module(
+            "module Main exposing (main)\n\
+             main : IO () = {\n\
+             \x20 let x = ;\n\
+             \x20 print (x +);\n\
+             \x20 x\n\
+             }\n\
+             other : Int = { let y = 1 }",
+        );
+        let spans = errors.iter().map(|error| error.span).collect::<Vec<_>>();
+        assert_eq!(
+            spans,
+            [Span::new(55, 55), Span::new(69, 69), Span::new(104, 104)]
+        );
+        assert_eq!(
+            errors[1].to_string(),
+            "expected one of `!`, `(`, `-`, `\\`, `if`, `let`, `match`, `{`, identifier, literal, \
+             found `)`\n  while parsing body of `main`"
+        );
+        assert_eq!(
+            errors[2].to_string(),
+            "expected one of `!=`, `%`, `&&`, `(`, `*`, `+`, `-`, `.`, `/`, `;`, `<`, `<=`, `==`, \
+             `>`, `>=`, `^`, `in`, `|>`, `||`, identifier, literal, found `}`\n  \
+             while parsing body of `other`"
+        );
+        assert_eq!(module.definitions.len(), 2);
+    }
+
+    #[test]
+    fn reports_lex_errors() {
+        let source = "module Util exposing (f)\nf = { g(\"a\\q\", '') }";
+        let (module, errors) = parse_module(source);
+        let escape = source.find('\\').unwrap();
+        let char_literal = source.find('\'').unwrap();
+        assert_eq!(
+            errors,
+            [
+                ParseError {
+                    kind: ParseErrorKind::Lex(LexErrorKind::InvalidEscape),
+                    span: Span::new(escape, escape + 1),
+                    context: Vec::new()
+                },
+                ParseError {
+                    kind: ParseErrorKind::Lex(LexErrorKind::InvalidCharLiteral),
+                    span: Span::new(char_literal, char_literal + 1),
+                    context: Vec::new()
+                }
+            ]
+        );
+        assert_eq!(errors[0].to_string(), "invalid escape sequence");
+        assert_eq!(module.definitions.len(), 1);
+    }
+
+    #[test]
+    fn negated_literals() {
+        let source = "module M exposing (..)\n\
+                      f(n) = { n-1u8 }\n\
+                      g(x) = x-1u32 + 2\n\
+                      min = -128i8 + -9223372036854775808\n\
+                      h(-1) = 128i8 + -1u32\n\
+                      k = match 0 { 255u8 | -1u8 => 0, n => n }";
+        let (module, errors) = parse_module(source);
+        assert_eq!(
+            function_names(&module.definitions),
+            ["f", "g", "min", "h", "k"]
+        );
+
+        let overflow = |literal: &str| {
+            let start = source.rfind(literal).unwrap();
+            Span::new(start, start + literal.len() - 1)
+        };
+        let spans = errors
+            .iter()
+            .map(|error| {
+                assert_eq!(
+                    error.kind,
+                    ParseErrorKind::Lex(LexErrorKind::IntegerOverflow)
+                );
+                error.span
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            spans,
+            [
+                overflow("128i8"),
+                overflow("-1u32"),
+                overflow("255u8 | -1u8 => 0")
+            ]
+        );
+    }
+
+    #[test]
+    fn describes_errors() {
+        let (_, errors) =
+            parse_module("module Util exposing (id)\nid : (x Number) => Number = { x }");
+        assert_eq!(
+            errors[0].to_string(),
+            "expected one of `(`, `)`, `,`, `.`, `:`, `::`, `<`, `as`, `|`, found `Number`\n  \
+             while parsing function signature of `id`"
+        );
+        assert_eq!(errors[0].context, ["function signature of `id`"]);
+        assert_eq!(errors[0].span, Span::new(34, 39));
+    }
+
+    #[test]
+    fn describes_skipped_optional_parts() {
+        // a variant pattern may go on with its path, its fields, an alias,
+        // another alternative or a guard
+        let (_, errors) = parse_module("module M exposing (f)\nf = match x { Some ) => 1 }");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].to_string(),
+            "expected one of `(`, `::`, `=>`, `as`, `if`, `{`, `|`, found `)`\n  \
+             while parsing match arm\n  while parsing match expression\n  \
+             while parsing clause of `f`"
+        );
+    }
+
+    #[test]
+    fn generic_signature() {
+        let tokens: Vec<_> = tokenize(": <T>(a: T, f: (T) => Vec<T>) => T").collect();
+        let (_, signature) = parse_function_signature(&tokens).unwrap();
+        assert_eq!(signature.type_params, [Ident("T")]);
+        assert_eq!(
+            signature.params[1].1,
+            Some(TypeExpr::Function {
+                params: vec![TypeExpr::Variable(Ident("T"))],
+                return_type: Box::new(TypeExpr::Named {
+                    path: vec![Ident("Vec")],
+                    args: vec![TypeExpr::Variable(Ident("T"))]
+                })
+            })
+        );
+        assert_eq!(signature.return_type, TypeExpr::Variable(Ident("T")));
+    }
+
+    #[test]
+    fn hello_examples() {
+        for source in [
+            include_str!("../../../examples/hello.meri"),
+            include_str!("../../../examples/module.meri"),
+        ] {
+            let module = parse_valid(source);
+            let [Definition::FunctionDefinition { sig: Some(sig), .. }] = &module.definitions[..]
+            else {
+                panic!("expected a single function definition");
+            };
+            assert_matches!(&sig.return_type, TypeExpr::Named { path, args } => {
+                assert_eq!(path, &[Ident("IO")]);
+                assert_eq!(args.len(), 1);
+            });
+        }
+    }
+
+    #[test]
+    fn unparenthesised_param() {
+        let tokens: Vec<_> = tokenize("square: x => Int = { x ^ 2 }").collect();
+        let (_, definition) = parse_function_definition(&tokens).unwrap();
+        assert_matches!(definition, Definition::FunctionDefinition { sig: Some(sig), .. } => {
+            assert_eq!(sig.params, [(Pattern::Binding(Ident("x")), None)]);
+        });
+
+        let tokens: Vec<_> = tokenize("id: x: Int => Int = { x }").collect();
+        let (_, definition) = parse_function_definition(&tokens).unwrap();
+        assert_matches!(definition, Definition::FunctionDefinition { sig: Some(sig), .. } => {
+            assert_eq!(
+                sig.params,
+                [(
+                    Pattern::Binding(Ident("x")),
+                    Some(TypeExpr::Named {
+                        path: vec![Ident("Int")],
+                        args: vec![]
+                    })
+                )]
+            );
+        });
+    }
+
+    #[test]
+    fn body_without_equal() {
+        let tokens: Vec<_> = tokenize("drop: x => Unit {\n  unit\n}").collect();
+        let (rest, definition) = parse_function_definition(&tokens).unwrap();
+        assert_eq!(rest[0].typ, TokenType::EOF);
+        assert_matches!(definition, Definition::FunctionDefinition { mut clauses, .. } => {
+            assert_eq!(
+                clauses.remove(0).body,
+                Expression::Variable {
+                    path: vec![Ident("unit")],
+                    span: Span::new(20, 23)
+                }
+            );
+        });
+    }
+
+    fn parse_valid(input: &str) -> Module<'_> {
+        let (module, errors) = parse_module(input);
+        assert_eq!(errors, [], "errors parsing {input:?}");
+        module
+    }
+
+    fn function_names<'a>(definitions: &[Definition<'a>]) -> Vec<&'a str> {
+        definitions
+            .iter()
+            .map(|definition| match definition {
+                Definition::FunctionDefinition { ident, .. } => ident.0,
+                Definition::TypeDefinition(_) | Definition::Error { .. } => {
+                    panic!("expected a function definition")
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn definitions_example() {
+        let source = include_str!("../../../examples/definitions.meri");
+        let (tokens, _) = significant_tokens(source);
+        let (items, errors) = parse_definitions(&tokens);
+        assert_eq!(errors, []);
+        let (definitions, errors) = group_clauses(items);
+        assert_eq!(
+            function_names(&definitions),
+            ["hello_swahili", "square", "drop", "square", "add"]
+        );
+        assert_matches!(&definitions[0], Definition::FunctionDefinition { clauses, .. } => {
+            assert_eq!(clauses.len(), 2);
+        });
+
+        // the example shows alternative ways of writing `square` and `add`
+        let kinds = errors
+            .iter()
+            .map(|error| error.kind.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                ParseErrorKind::NonContiguousClauses,
+                ParseErrorKind::MisplacedSignature,
+                ParseErrorKind::MisplacedSignature
+            ]
+        );
+
+        // comments start with `--`, so the `//` in the body of the generic
+        // `add` is a syntax error
+        let mut errors = Vec::new();
+        collect_errors(&tokens, &definitions, &mut errors);
+        let slash = source.find("//").unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].span, Span::new(slash, slash));
+        assert_eq!(errors[0].context, ["body of `add`"]);
+    }
+
+    #[test]
+    fn multi_clause_module() {
+        let module = parse_valid(
+            "module Fib exposing (fib)\n\
+             \n\
+             --- The nth fibonacci number\n\
+             fib: (n: Int) => Int\n\
+             fib(0) = 0\n\
+             fib(1) = 1\n\
+             fib(n) =\n  fib(n - 1) + fib(n - 2)\n",
+        );
+        let [Definition::FunctionDefinition {
+            doc,
+            sig: Some(_),
+            clauses,
+            ..
+        }] = &module.definitions[..]
+        else {
+            panic!("expected a single function definition");
+        };
+        assert_eq!(doc.as_deref(), Some("The nth fibonacci number"));
+        assert_eq!(
+            clauses
+                .iter()
+                .map(|clause| clause.params.clone())
+                .collect::<Vec<_>>(),
+            [
+                vec![Pattern::Literal(Literal::Int(0, None))],
+                vec![Pattern::Literal(Literal::Int(1, None))],
+                vec![Pattern::Binding(Ident("n"))]
+            ]
+        );
+        assert_matches!(&clauses[2].body, Expression::Binary { .. });
+    }
+
+    #[test]
+    fn misplaced_doc_comments() {
+        let module = parse_valid(
+            "--- The module\n\
+             module M exposing (..)\n\
+             --- Not an import\n\
+             import Stdio\n\
+             --- Doubles\n\
+             double: x => Int = {\n\
+             \x20 --- explain the next step\n\
+             \x20 x * 2\n\
+             }\n\
+             half(x) =\n\
+             --- at the start of a line\n\
+             \x20 x / 2\n\
+             --- documents nothing",
+        );
+        assert_eq!(function_names(&module.definitions), ["double", "half"]);
+        assert_eq!(module.definitions[0].doc(), Some("Doubles"));
+        assert_eq!(module.definitions[1].doc(), None);
+    }
+
+    #[test]
+    fn module_clause_errors() {
+        let (_, errors) = parse_module("module M exposing (..)\nf(0) = 0\nf(a, b) = a");
+        assert_matches!(
+            &errors[..],
+            [ParseError {
+                kind: ParseErrorKind::ArityMismatch {
+                    expected: 1,
+                    found: 2
+                },
+                ..
+            }]
+        );
+    }
+
+    #[test]
+    fn functions_example() {
+        let module = parse_valid(include_str!("../../../examples/functions.meri"));
+        assert_eq!(module.header.exposing, Exposing::All);
+        assert_eq!(
+            function_names(&module.definitions),
+            ["PI", "square", "log", "apply"]
+        );
+        assert_matches!(
+            &module.definitions[3],
+            Definition::FunctionDefinition { clauses, .. } => {
+                assert_matches!(clauses[0].body, Expression::Unit { .. });
+            }
+        );
+    }
+
+    #[test]
+    fn keyword_is_not_ident() {
+        let tokens: Vec<_> = tokenize("let : Int = { 1 }").collect();
+        assert!(parse_function_definition(&tokens).is_err());
+    }
 }
@@ -0,0 +1,241 @@
+//! The header at the start of every module, declaring its name and exports,
+//! followed by its imports
+//! ```meri
+//! module Main exposing (main)
+//!
+//! import Foo.Bar as B exposing (x, Type(..))
+//! ```
+
+use meri_ast::{Export, Exposing, Ident, Import, ModuleHeader};
+use nom::{
+    combinator::{cut, opt},
+    multi::{many0, separated_list1},
+    sequence::{delimited, preceded, tuple},
+    Parser,
+};
+use nom_supreme::ParserExt;
+
+use crate::{
+    ident, newlines,
+    token::{Token, TokenType},
+    token_type, ParseResult,
+};
+
+pub(crate) fn parse_module_header<'a, 'b>(
+    input: &'a [Token<'b>],
+) -> ParseResult<'a, 'b, ModuleHeader<'b>> {
+    let (input, _) = token_type(TokenType::Module)(input)?;
+    let (input, path) = parse_module_path.context("module header").parse(input)?;
+    let (input, exposing) = parse_exposing.context("module header").parse(input)?;
+    let (input, imports) = many0(preceded(newlines, parse_import))(input)?;
+
+    let name = path
+        .iter()
+        .map(|Ident(segment)| *segment)
+        .collect::<Vec<_>>()
+        .join(".");
+
+    Ok((
+        input,
+        ModuleHeader {
+            name,
+            exposing,
+            imports,
+        },
+    ))
+}
+
+/// Names separated by `.` e.g. `Foo.Bar`
+fn parse_module_path<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Vec<Ident<'b>>> {
+    separated_list1(token_type(TokenType::Dot), ident)(input)
+}
+
+/// `exposing (..)` or `exposing (item, ...)`
+fn parse_exposing<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Exposing<'b>> {
+    let (input, _) = token_type(TokenType::Exposing)(input)?;
+    cut(parse_exposed)(input)
+}
+
+/// The parenthesised list following `exposing`
+fn parse_exposed<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Exposing<'b>> {
+    let (input, _) = token_type(TokenType::Lparen)(input)?;
+
+    let (input, exposing) = match token_type(TokenType::DoubleDot)(input) {
+        Ok((input, _)) => (input, Exposing::All),
+        Err(_) => {
+            let (input, items) =
+                separated_list1(token_type(TokenType::Comma), parse_export)(input)?;
+            (input, Exposing::Items(items))
+        }
+    };
+
+    let (input, _) = token_type(TokenType::RParen)(input)?;
+    Ok((input, exposing))
+}
+
+/// `value`, `Type` or `Type(..)`
+fn parse_export<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Export<'b>> {
+    let (input, ident) = ident(input)?;
+    if !ident.0.starts_with(|c: char| c.is_uppercase()) {
+        return Ok((input, Export::Value(ident)));
+    }
+
+    let (input, variants) = opt(delimited(
+        token_type(TokenType::Lparen),
+        token_type(TokenType::DoubleDot),
+        token_type(TokenType::RParen),
+    ))(input)?;
+
+    Ok((
+        input,
+        Export::Type {
+            ident,
+            variants: variants.is_some(),
+        },
+    ))
+}
+
+/// `import Path as Alias exposing (...)` with the alias and exposing list
+/// optional. Anything following `import` must form the rest of the import, so
+/// that a broken import is reported as such rather than as the start of the
+/// definitions
+fn parse_import<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Import<'b>> {
+    let (input, _) = token_type(TokenType::Import)(input)?;
+    let (input, (path, alias, exposing)) = cut(tuple((
+        parse_module_path,
+        opt(preceded(token_type(TokenType::As), cut(ident))),
+        opt(parse_exposing),
+    )))
+    .context("import")
+    .parse(input)?;
+
+    Ok((
+        input,
+        Import {
+            path,
+            alias,
+            exposing,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use meri_ast::{Export, Exposing, Ident, Import, ModuleHeader};
+
+    use crate::{significant_tokens, span::Span, token::TokenType};
+
+    use super::parse_module_header;
+
+    fn parse(input: &str) -> ModuleHeader<'_> {
+        let (tokens, _) = significant_tokens(input);
+        let (rest, header) = parse_module_header(&tokens).unwrap();
+        assert_eq!(rest[0].typ, TokenType::EOF, "unparsed input in {input:?}");
+        header
+    }
+
+    #[test]
+    fn exposing_values() {
+        assert_eq!(
+            parse("module Main exposing (main)"),
+            ModuleHeader {
+                name: "Main".to_string(),
+                exposing: Exposing::Items(vec![Export::Value(Ident("main"))]),
+                imports: vec![]
+            }
+        );
+    }
+
+    #[test]
+    fn exposing_everything() {
+        let header = parse("module Data.Functions exposing (..)");
+        assert_eq!(header.name, "Data.Functions");
+        assert_eq!(header.exposing, Exposing::All);
+    }
+
+    #[test]
+    fn exposing_types() {
+        assert_eq!(
+            parse("module Colors exposing (Color(..), Palette, mix)").exposing,
+            Exposing::Items(vec![
+                Export::Type {
+                    ident: Ident("Color"),
+                    variants: true
+                },
+                Export::Type {
+                    ident: Ident("Palette"),
+                    variants: false
+                },
+                Export::Value(Ident("mix")),
+            ])
+        );
+    }
+
+    #[test]
+    fn imports() {
+        let header = parse(
+            "module Main exposing (main)\n\
+             import Stdio\n\
+             import Foo.Bar as B exposing (x, Type(..))\n\
+             import Math exposing (..)",
+        );
+        assert_eq!(
+            header.imports,
+            [
+                Import {
+                    path: vec![Ident("Stdio")],
+                    alias: None,
+                    exposing: None
+                },
+                Import {
+                    path: vec![Ident("Foo"), Ident("Bar")],
+                    alias: Some(Ident("B")),
+                    exposing: Some(Exposing::Items(vec![
+                        Export::Value(Ident("x")),
+                        Export::Type {
+                            ident: Ident("Type"),
+                            variants: true
+                        }
+                    ]))
+                },
+                Import {
+                    path: vec![Ident("Math")],
+                    alias: None,
+                    exposing: Some(Exposing::All)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn broken_import() {
+        let (tokens, _) =
+            significant_tokens("module Main exposing (main)\nimport Foo as\nmain = 1");
+        let Err(nom::Err::Failure(error)) = parse_module_header(&tokens) else {
+            panic!("expected the import to fail");
+        };
+        let error = error.into_parse_error();
+        assert_eq!(
+            error.to_string(),
+            "expected identifier, found end of definition\n  while parsing import"
+        );
+        assert_eq!(error.span, Span::new(42, 45));
+    }
+
+    #[test]
+    fn rejects_invalid_headers() {
+        for input in [
+            "module Main",
+            "module Main exposing ()",
+            "module exposing (main)",
+            "module Main exposing (Color(Red))",
+        ] {
+            let (tokens, _) = significant_tokens(input);
+            let result = parse_module_header(&tokens);
+            assert!(
+                result.map_or(true, |(rest, _)| rest[0].typ != TokenType::EOF),
+                "{input:?} should not parse"
+            );
+        }
+    }
+}
@@ -0,0 +1,310 @@
+//! Patterns, used in function parameters, `let` bindings and match arms.
+//!
+//! An identifier starting with an uppercase letter, or a qualified path, names
+//! a variant of an enum while any other identifier binds a variable
+//! ```meri
+//! Some(Color::Rgb(r, _, 0) | Color::Hsv { a: r, .. }) as color
+//! ```
+
+use meri_ast::{Ident, Pattern};
+use nom::{combinator::opt, multi::separated_list1, sequence::preceded};
+
+use crate::{
+    comma_list,
+    error::{record, Expected, TokenError},
+    ident, literal, negated,
+    token::{Token, TokenType},
+    token_type, ParseResult,
+};
+
+/// A  pattern used in match statements and in binding fucntion arguments
+pub(crate) fn parse_pattern<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Pattern<'b>> {
+    let (rest, pattern) = parse_or_pattern(input)?;
+    let Ok((rest, ident)) = preceded(token_type(TokenType::As), ident)(rest) else {
+        return Ok((rest, pattern));
+    };
+
+    Ok((
+        rest,
+        Pattern::As {
+            pattern: Box::new(pattern),
+            ident,
+        },
+    ))
+}
+
+/// Alternatives separated by `|`
+fn parse_or_pattern<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Pattern<'b>> {
+    let (rest, mut alternatives) =
+        separated_list1(token_type(TokenType::Vbar), parse_primary_pattern)(input)?;
+
+    let pattern = if alternatives.len() == 1 {
+        alternatives.remove(0)
+    } else {
+        Pattern::Or(alternatives)
+    };
+
+    Ok((rest, pattern))
+}
+
+fn parse_primary_pattern<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Pattern<'b>> {
+    match input.first().map(|t| &t.typ) {
+        Some(TokenType::Ident("_")) => Ok((&input[1..], Pattern::Wildcard)),
+        Some(TokenType::Ident(_)) => {
+            let (rest, mut path) = parse_path(input)?;
+            if path.len() == 1 && !is_variant_name(path[0].0) {
+                return Ok((rest, Pattern::Binding(path.remove(0))));
+            }
+            parse_variant_fields(rest, path)
+        }
+        Some(TokenType::Lparen) => parse_tuple_pattern(input),
+        Some(TokenType::LBrace) => parse_record_pattern(input),
+        Some(TokenType::Minus) => {
+            let (rest, value) = preceded(token_type(TokenType::Minus), literal)(input)?;
+            match negated(&value) {
+                Some(value) => Ok((rest, Pattern::Literal(value))),
+                None => Err(nom::Err::Error(TokenError::expected(
+                    &input[1..],
+                    vec![Expected::Literal],
+                ))),
+            }
+        }
+        Some(_) => {
+            let (rest, literal) = literal(input)?;
+            Ok((rest, Pattern::Literal(literal)))
+        }
+        None => Err(nom::Err::Error(TokenError::expected(input, Vec::new()))),
+    }
+}
+
+fn is_variant_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_uppercase())
+}
+
+/// The fields following the name of a variant, `(fields...)`, `{ fields... }` or none
+fn parse_variant_fields<'a, 'b>(
+    input: &'a [Token<'b>],
+    path: Vec<Ident<'b>>,
+) -> ParseResult<'a, 'b, Pattern<'b>> {
+    let (rest, fields) = match input.first().map(|t| &t.typ) {
+        Some(TokenType::Lparen) => preceded(
+            token_type(TokenType::Lparen),
+            comma_list(parse_pattern, TokenType::RParen),
+        )(input)?,
+        Some(TokenType::LBrace) => {
+            let (rest, record) = parse_record_pattern(input)?;
+            (rest, vec![record])
+        }
+        _ => {
+            record(input, || {
+                Expected::tokens(&[TokenType::Lparen, TokenType::LBrace])
+            });
+            (input, Vec::new())
+        }
+    };
+
+    Ok((rest, Pattern::DataVariant { path, fields }))
+}
+
+/// Identifiers separated by `::`
+fn parse_path<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Vec<Ident<'b>>> {
+    separated_list1(token_type(TokenType::DoubleColon), ident)(input)
+}
+
+/// `()`, `(pattern)` or `(a, b, ...)`
+fn parse_tuple_pattern<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Pattern<'b>> {
+    let (rest, mut elements) = preceded(
+        token_type(TokenType::Lparen),
+        comma_list(parse_pattern, TokenType::RParen),
+    )(input)?;
+
+    let pattern = if elements.len() == 1 {
+        elements.remove(0)
+    } else {
+        Pattern::Tuple(elements)
+    };
+
+    Ok((rest, pattern))
+}
+
+/// `{ field, field: pattern, .. }`
+fn parse_record_pattern<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Pattern<'b>> {
+    let (mut rest, _) = token_type(TokenType::LBrace)(input)?;
+
+    let mut fields = Vec::new();
+    let mut ignores_rest = false;
+    loop {
+        if let Ok((after, _)) = token_type(TokenType::DoubleDot)(rest) {
+            ignores_rest = true;
+            rest = after;
+            break;
+        }
+        let Ok((after, name)) = ident(rest) else {
+            break;
+        };
+        let (after, pattern) = opt(preceded(token_type(TokenType::Colon), parse_pattern))(after)?;
+        fields.push((name, pattern.unwrap_or(Pattern::Binding(name))));
+
+        match token_type(TokenType::Comma)(after) {
+            Ok((after, _)) => rest = after,
+            Err(_) => {
+                rest = after;
+                break;
+            }
+        }
+    }
+
+    let (rest, _) = token_type(TokenType::RBrace)(rest)?;
+
+    Ok((
+        rest,
+        Pattern::Record {
+            fields,
+            rest: ignores_rest,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use meri_ast::{Ident, Literal, Pattern};
+
+    use crate::{lexer::tokenize, token::TokenType};
+
+    use super::parse_pattern;
+
+    fn parse(input: &str) -> Pattern<'_> {
+        let tokens = tokenize(input).collect::<Vec<_>>();
+        let (rest, pattern) = parse_pattern(&tokens).unwrap();
+        assert_eq!(rest[0].typ, TokenType::EOF, "unparsed input in {input:?}");
+        pattern
+    }
+
+    fn binding(name: &str) -> Pattern<'_> {
+        Pattern::Binding(Ident(name))
+    }
+
+    fn variant<'a>(path: &[&'a str], fields: Vec<Pattern<'a>>) -> Pattern<'a> {
+        Pattern::DataVariant {
+            path: path.iter().map(|segment| Ident(segment)).collect(),
+            fields,
+        }
+    }
+
+    #[test]
+    fn simple_patterns() {
+        assert_eq!(parse("x"), binding("x"));
+        assert_eq!(parse("_x"), binding("_x"));
+        assert_eq!(parse("_"), Pattern::Wildcard);
+        assert_eq!(parse("0"), Pattern::Literal(Literal::Int(0, None)));
+        assert_eq!(parse("-1"), Pattern::Literal(Literal::Int(-1, None)));
+        assert_eq!(
+            parse("\"hi\""),
+            Pattern::Literal(Literal::String("hi".to_string()))
+        );
+        assert_eq!(parse("true"), Pattern::Literal(Literal::Bool(true)));
+    }
+
+    #[test]
+    fn constructor_patterns() {
+        assert_eq!(parse("None"), variant(&["None"], vec![]));
+        assert_eq!(parse("Some(x)"), variant(&["Some"], vec![binding("x")]));
+        assert_eq!(
+            parse("Color::Rgb(r, _, 0)"),
+            variant(
+                &["Color", "Rgb"],
+                vec![
+                    binding("r"),
+                    Pattern::Wildcard,
+                    Pattern::Literal(Literal::Int(0, None))
+                ]
+            )
+        );
+        assert_eq!(parse("color::red"), variant(&["color", "red"], vec![]));
+        assert_eq!(
+            parse("Some(Some(x))"),
+            variant(&["Some"], vec![variant(&["Some"], vec![binding("x")])])
+        );
+    }
+
+    #[test]
+    fn record_patterns() {
+        assert_eq!(
+            parse("{ r, g: green, .. }"),
+            Pattern::Record {
+                fields: vec![(Ident("r"), binding("r")), (Ident("g"), binding("green"))],
+                rest: true
+            }
+        );
+        assert_eq!(
+            parse("{ r, g, b, }"),
+            Pattern::Record {
+                fields: vec![
+                    (Ident("r"), binding("r")),
+                    (Ident("g"), binding("g")),
+                    (Ident("b"), binding("b"))
+                ],
+                rest: false
+            }
+        );
+        assert_eq!(
+            parse("Hsv { a: 0, .. }"),
+            variant(
+                &["Hsv"],
+                vec![Pattern::Record {
+                    fields: vec![(Ident("a"), Pattern::Literal(Literal::Int(0, None)))],
+                    rest: true
+                }]
+            )
+        );
+    }
+
+    #[test]
+    fn tuple_patterns() {
+        assert_eq!(parse("()"), Pattern::Tuple(vec![]));
+        assert_eq!(parse("(x)"), binding("x"));
+        assert_eq!(
+            parse("(x, (y, _))"),
+            Pattern::Tuple(vec![
+                binding("x"),
+                Pattern::Tuple(vec![binding("y"), Pattern::Wildcard])
+            ])
+        );
+    }
+
+    #[test]
+    fn or_and_as_patterns() {
+        assert_eq!(
+            parse("Some(0) | None as original"),
+            Pattern::As {
+                pattern: Box::new(Pattern::Or(vec![
+                    variant(&["Some"], vec![Pattern::Literal(Literal::Int(0, None))]),
+                    variant(&["None"], vec![])
+                ])),
+                ident: Ident("original")
+            }
+        );
+        assert_eq!(
+            parse("Some(1 | 2)"),
+            variant(
+                &["Some"],
+                vec![Pattern::Or(vec![
+                    Pattern::Literal(Literal::Int(1, None)),
+                    Pattern::Literal(Literal::Int(2, None))
+                ])]
+            )
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        for input in ["{ r: }", "Some(", "x as", "| x", "{ .., r }"] {
+            let tokens = tokenize(input).collect::<Vec<_>>();
+            let result = parse_pattern(&tokens);
+            assert!(
+                result.map_or(true, |(rest, _)| rest[0].typ != TokenType::EOF),
+                "{input:?} should not parse"
+            );
+        }
+    }
+}
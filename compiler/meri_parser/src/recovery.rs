@@ -0,0 +1,243 @@
+//! Recovery from syntax errors, so that all the errors in a module are
+//! reported at once.
+//!
+//! A definition that fails to parse is skipped up to the start of the next
+//! one, which always begins on a new line, and replaced by
+//! `Definition::Error`. Within a block, a broken statement is skipped up to
+//! the `;` or `}` ending it and replaced by `Expression::Error`
+//! ```meri
+//! main : IO () = {
+//!   let x = ;   -- skipped up to the `;`
+//!   print x
+//! }
+//! ```
+
+use meri_ast::{Definition, Expression, Literal, Pattern, Statement};
+
+use crate::{
+    error::{recording_expected, Expected, TokenError},
+    expr::{consumed_span, parse_statement},
+    lexer::{integer_range, LexErrorKind},
+    span::Span,
+    token::{Token, TokenType},
+    ParseError, ParseErrorKind,
+};
+
+/// The tokens following a broken statement, starting at the `;` or `}` that
+/// ends it. Stops early at the end of the definition if the block is never closed
+pub(crate) fn skip_statement<'a, 'b>(input: &'a [Token<'b>]) -> &'a [Token<'b>] {
+    let mut depth = 0usize;
+    let mut rest = input;
+
+    while let Some((token, after)) = rest.split_first() {
+        match token.typ {
+            TokenType::Semicolon | TokenType::RBrace if depth == 0 => break,
+            TokenType::Newline | TokenType::EOF => break,
+            TokenType::Lparen | TokenType::LBrace | TokenType::LBracket => depth += 1,
+            TokenType::RParen | TokenType::RBrace | TokenType::RBracket => {
+                depth = depth.saturating_sub(1)
+            }
+            _ => {}
+        }
+        rest = after;
+    }
+
+    rest
+}
+
+/// The tokens following a broken definition, starting at the next definition
+/// or the end of the input. `input` starts at the token the definition broke
+/// at, which is skipped unless it already ends the definition
+pub(crate) fn skip_definition<'a, 'b>(input: &'a [Token<'b>]) -> &'a [Token<'b>] {
+    let mut rest = match input.split_first() {
+        Some((token, after)) if !matches!(token.typ, TokenType::Newline | TokenType::EOF) => after,
+        _ => input,
+    };
+
+    while let Some((token, after)) = rest.split_first() {
+        if matches!(token.typ, TokenType::Newline | TokenType::EOF) {
+            break;
+        }
+        rest = after;
+    }
+
+    rest
+}
+
+/// A placeholder for the tokens skipped from `input` to `rest`, or for the
+/// token at which parsing failed if none were skipped
+pub(crate) fn error_expression<'b>(input: &[Token<'b>], rest: &[Token<'b>]) -> Expression<'b> {
+    let span = if input.len() == rest.len() {
+        input.first().map_or(Span::empty(), |t| t.span)
+    } else {
+        consumed_span(input, rest)
+    };
+
+    Expression::Error { span }
+}
+
+/// Reports the `Expression::Error` placeholders in the bodies of
+/// `definitions`, parsed from `tokens`, along with the integer literals that
+/// do not fit their type.
+///
+/// Whether a literal is negated is only known once its expression is parsed,
+/// so `-128i8` fits while `128i8` and `-1u32` do not. A literal in a pattern
+/// is reported at the clause, binding, arm or lambda the pattern belongs to
+pub(crate) fn collect_errors(
+    tokens: &[Token<'_>],
+    definitions: &[Definition<'_>],
+    errors: &mut Vec<ParseError>,
+) {
+    for definition in definitions {
+        let Definition::FunctionDefinition { ident, clauses, .. } = definition else {
+            continue;
+        };
+
+        let mut spans = Vec::new();
+        let mut overflows = Vec::new();
+        for clause in clauses {
+            for param in &clause.params {
+                pattern_overflows(param, clause.span, &mut overflows);
+            }
+            expression_errors(&clause.body, &mut spans, &mut overflows);
+        }
+        errors.extend(spans.into_iter().map(|span| {
+            let mut error = statement_error(tokens, span);
+            error.context.insert(0, format!("body of `{}`", ident.0));
+            error
+        }));
+        errors.extend(overflows.into_iter().map(|span| ParseError {
+            kind: ParseErrorKind::Lex(LexErrorKind::IntegerOverflow),
+            span,
+            context: Vec::new(),
+        }));
+    }
+}
+
+/// The error in the broken statement starting at `span`, found by parsing it
+/// again. The placeholder only records the tokens that were skipped
+fn statement_error(tokens: &[Token<'_>], span: Span) -> ParseError {
+    let start = tokens.partition_point(|token| token.span.start < span.start);
+    let input = &tokens[start..];
+
+    let (parsed, expected) = recording_expected(|| parse_statement(input));
+    let error = match parsed {
+        Err(nom::Err::Error(error) | nom::Err::Failure(error)) => error,
+        Err(nom::Err::Incomplete(_)) => unreachable!("the token parsers are all complete"),
+        // the statement itself is fine but is not followed by what may end it
+        Ok((rest, statement)) => {
+            let expected = match statement {
+                Statement::Let(_) => [TokenType::Semicolon, TokenType::In],
+                Statement::Expression(_) => [TokenType::Semicolon, TokenType::RBrace],
+            };
+            TokenError::expected(rest, expected.iter().map(Expected::token).collect())
+        }
+    };
+
+    error.with_recorded(&expected).into_parse_error()
+}
+
+/// The spans of the `Expression::Error` placeholders in `expr` and of the
+/// integer literals in it that overflow
+fn expression_errors(expr: &Expression<'_>, errors: &mut Vec<Span>, overflows: &mut Vec<Span>) {
+    match expr {
+        Expression::Error { span } => errors.push(*span),
+        Expression::Literal { value, span } if out_of_range(value) => overflows.push(*span),
+        Expression::Unit { .. } | Expression::Literal { .. } | Expression::Variable { .. } => {}
+        Expression::Tuple { elements, .. } => elements
+            .iter()
+            .for_each(|e| expression_errors(e, errors, overflows)),
+        Expression::Call { func, args, .. } => {
+            expression_errors(func, errors, overflows);
+            args.iter()
+                .for_each(|arg| expression_errors(arg, errors, overflows));
+        }
+        Expression::Field { expr, .. } | Expression::Unary { expr, .. } => {
+            expression_errors(expr, errors, overflows)
+        }
+        Expression::Binary { lhs, rhs, .. } => {
+            expression_errors(lhs, errors, overflows);
+            expression_errors(rhs, errors, overflows);
+        }
+        Expression::Let { binding, body, .. } => {
+            pattern_overflows(&binding.pattern, binding.span, overflows);
+            expression_errors(&binding.value, errors, overflows);
+            expression_errors(body, errors, overflows);
+        }
+        Expression::If {
+            cond,
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            expression_errors(cond, errors, overflows);
+            expression_errors(then_branch, errors, overflows);
+            expression_errors(else_branch, errors, overflows);
+        }
+        Expression::Match {
+            scrutinee, arms, ..
+        } => {
+            expression_errors(scrutinee, errors, overflows);
+            for arm in arms {
+                pattern_overflows(&arm.pattern, arm.span, overflows);
+                if let Some(guard) = &arm.guard {
+                    expression_errors(guard, errors, overflows);
+                }
+                expression_errors(&arm.body, errors, overflows);
+            }
+        }
+        Expression::Lambda { params, body, span } => {
+            for (pattern, _) in params {
+                pattern_overflows(pattern, *span, overflows);
+            }
+            expression_errors(body, errors, overflows);
+        }
+        Expression::Record { fields, .. } => fields
+            .iter()
+            .for_each(|(_, value)| expression_errors(value, errors, overflows)),
+        Expression::Block {
+            statements, result, ..
+        } => {
+            for statement in statements {
+                match statement {
+                    Statement::Let(binding) => {
+                        pattern_overflows(&binding.pattern, binding.span, overflows);
+                        expression_errors(&binding.value, errors, overflows);
+                    }
+                    Statement::Expression(expr) => expression_errors(expr, errors, overflows),
+                }
+            }
+            expression_errors(result, errors, overflows);
+        }
+    }
+}
+
+/// Adds `span`, of what binds `pattern`, for every integer literal in the
+/// pattern that overflows
+fn pattern_overflows(pattern: &Pattern<'_>, span: Span, overflows: &mut Vec<Span>) {
+    match pattern {
+        Pattern::Literal(literal) if out_of_range(literal) => overflows.push(span),
+        Pattern::Wildcard | Pattern::Binding(_) | Pattern::Literal(_) => {}
+        Pattern::DataVariant {
+            fields: patterns, ..
+        }
+        | Pattern::Tuple(patterns)
+        | Pattern::Or(patterns) => patterns
+            .iter()
+            .for_each(|pattern| pattern_overflows(pattern, span, overflows)),
+        Pattern::Record { fields, .. } => fields
+            .iter()
+            .for_each(|(_, pattern)| pattern_overflows(pattern, span, overflows)),
+        Pattern::As { pattern, .. } => pattern_overflows(pattern, span, overflows),
+    }
+}
+
+/// Whether `literal` is an integer outside the range of its type
+fn out_of_range(literal: &Literal) -> bool {
+    let Literal::Int(int, suffix) = *literal else {
+        return false;
+    };
+    let (min, max) = integer_range(suffix);
+
+    !(min..=max).contains(&int)
+}
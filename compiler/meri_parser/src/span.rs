@@ -1,15 +1,145 @@
+pub use meri_ast::Span;
+
+/// A position in the source code. All values are zero based
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct Span {
-    pub start: usize,
-    pub end: usize,
+pub struct Location {
+    pub line: usize,
+    /// Column counted in bytes from the start of the line
+    pub column: usize,
+    /// Column counted in UTF-16 code units from the start of the line, as
+    /// expected by most editors
+    pub utf16_column: usize,
+}
+
+/// Converts byte offsets in a source file to line and column locations
+#[derive(Debug)]
+pub struct SourceMap<'a> {
+    source: &'a str,
+    /// Byte offsets of the start of every line
+    line_starts: Vec<usize>,
+}
+
+impl<'a> SourceMap<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+
+        Self {
+            source,
+            line_starts,
+        }
+    }
+
+    /// The location of the char containing the byte at `offset`.
+    /// Offsets past the end of the source resolve to the end of the source
+    pub fn location(&self, offset: usize) -> Location {
+        let mut offset = offset.min(self.source.len());
+        while !self.source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let line_start = self.line_starts[line];
+
+        Location {
+            line,
+            column: offset - line_start,
+            utf16_column: self.source[line_start..offset].encode_utf16().count(),
+        }
+    }
+
+    /// The start location and the exclusive end location of `span`
+    pub fn span_range(&self, span: Span) -> (Location, Location) {
+        (self.location(span.start), self.location(span.end + 1))
+    }
+
+    /// The text of the line at `line` without its line ending
+    pub fn line_text(&self, line: usize) -> Option<&'a str> {
+        let start = *self.line_starts.get(line)?;
+        let end = self
+            .line_starts
+            .get(line + 1)
+            .map_or(self.source.len(), |next| next - 1);
+
+        Some(self.source[start..end].trim_end_matches('\r'))
+    }
 }
 
-impl Span {
-    pub fn new(start: usize, end: usize) -> Self {
-        Self { start, end }
+#[cfg(test)]
+mod tests {
+    use super::{Location, SourceMap, Span};
+
+    #[test]
+    fn ascii_locations() {
+        let map = SourceMap::new("ab\ncd\n");
+
+        assert_eq!(
+            map.location(0),
+            Location {
+                line: 0,
+                column: 0,
+                utf16_column: 0
+            }
+        );
+        assert_eq!(
+            map.location(4),
+            Location {
+                line: 1,
+                column: 1,
+                utf16_column: 1
+            }
+        );
+        assert_eq!(
+            map.location(6),
+            Location {
+                line: 2,
+                column: 0,
+                utf16_column: 0
+            }
+        );
+    }
+
+    #[test]
+    fn non_ascii_locations() {
+        // `é` takes two bytes in UTF-8 and `😀` four, which is two UTF-16 code units
+        let source = "-- é😀\nx";
+        let map = SourceMap::new(source);
+
+        let x = source.find('x').unwrap();
+        assert_eq!(
+            map.location(x),
+            Location {
+                line: 1,
+                column: 0,
+                utf16_column: 0
+            }
+        );
+
+        let (start, end) = map.span_range(Span::new(5, 8));
+        assert_eq!(
+            start,
+            Location {
+                line: 0,
+                column: 5,
+                utf16_column: 4
+            }
+        );
+        assert_eq!(
+            end,
+            Location {
+                line: 0,
+                column: 9,
+                utf16_column: 6
+            }
+        );
     }
 
-    pub fn empty() -> Self {
-        Self { start: 0, end: 0 }
+    #[test]
+    fn line_text() {
+        let map = SourceMap::new("first\r\nsecond");
+        assert_eq!(map.line_text(0), Some("first"));
+        assert_eq!(map.line_text(1), Some("second"));
+        assert_eq!(map.line_text(2), None);
     }
 }
@@ -2,39 +2,64 @@ use std::fmt::{write, Display};
 
 use nom::InputTake;
 
-use crate::span::Span;
+pub use meri_ast::NumberSuffix;
 
-pub fn try_into_keyword(i: &str) -> Option<TokenType> {
+use crate::{lexer::LexErrorKind, span::Span};
+
+pub fn try_into_keyword(i: &str) -> Option<TokenType<'_>> {
     match i {
         "type" => Some(TokenType::Type),
         "typealias" => Some(TokenType::TypeAlias),
         "module" => Some(TokenType::Module),
         "exposing" => Some(TokenType::Exposing),
         "import" => Some(TokenType::Import),
+        "union" => Some(TokenType::Union),
+        "record" => Some(TokenType::Record),
+        "struct" => Some(TokenType::Struct),
+        "let" => Some(TokenType::Let),
+        "in" => Some(TokenType::In),
+        "if" => Some(TokenType::If),
+        "then" => Some(TokenType::Then),
+        "else" => Some(TokenType::Else),
+        "match" => Some(TokenType::Match),
+        "case" => Some(TokenType::Case),
+        "of" => Some(TokenType::Of),
+        "as" => Some(TokenType::As),
+        "where" => Some(TokenType::Where),
+        "true" => Some(TokenType::True),
+        "false" => Some(TokenType::False),
         _ => None,
     }
 }
 
 /// A representation of a token
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Token<'a> {
     pub typ: TokenType<'a>,
     pub span: Span,
 }
 
 /// A set of all the tokens that are recognized in the Meri language
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum TokenType<'a> {
     /// An identifier. The value for a name
     Ident(&'a str),
-    /// Token for an Integer
-    IntegerLit(i64),
-    /// Token for floating point number
-    FloatLit(f64),
-    /// Token for a string literal, represented as a set of characters delimited by quotes
-    StringLit(&'a str),
+    /// Token for an Integer and its optional type suffix e.g. `10i32`. Wide
+    /// enough for the values of every integer type, up to `u64::MAX`
+    IntegerLit(i128, Option<NumberSuffix>),
+    /// Token for floating point number and its optional type suffix e.g. `3.0f32`
+    FloatLit(f64, Option<NumberSuffix>),
+    /// Token for a string literal, represented as a set of characters delimited by quotes.
+    /// Holds the decoded value i.e. with escape sequences already processed
+    StringLit(String),
+    /// Token for a character literal e.g. `'a'` or `'\n'`
+    CharLit(char),
     /// Token for Comments. These will be filtered out during lexing
     Comment(&'a str),
+    /// Token for doc comments starting with `---` or `--|`. Holds the text of
+    /// the comment without the marker. These document the definition that follows
+    DocComment(&'a str),
 
     /// Token for a left parenthesis `(`
     Lparen,
@@ -50,6 +75,10 @@ pub enum TokenType<'a> {
     LAngleBracket,
     /// Token for right angle bracket / grater than sign `>`
     RAngleBracket,
+    /// Token for a left square bracket `[`
+    LBracket,
+    /// Token for a right square bracket `]`
+    RBracket,
 
     /// Token for a single equal sign `=`
     Equal,
@@ -71,11 +100,44 @@ pub enum TokenType<'a> {
     Amper,
     ///Token for logical not '!'
     Exclam,
+    /// Token for a semicolon `;`
+    Semicolon,
+    /// Token for the at sign `@`
+    At,
+    /// Token for a question mark `?`
+    Question,
+    /// Token for a hash `#`
+    Hash,
 
     /// Token for the slash  `/`
     Slash,
     /// Token for the slash  `\`
     BackSlash,
+    /// Token for the caret `^`
+    Caret,
+
+    /// Token for a fat arrow `=>`
+    FatArrow,
+    /// Token for a thin arrow `->`
+    Arrow,
+    /// Token for the equality operator `==`
+    DoubleEqual,
+    /// Token for the inequality operator `!=`
+    NotEqual,
+    /// Token for the less than or equal operator `<=`
+    LessEqual,
+    /// Token for the greater than or equal operator `>=`
+    GreaterEqual,
+    /// Token for logical and `&&`
+    DoubleAmper,
+    /// Token for logical or `||`
+    DoubleVbar,
+    /// Token for the path separator `::`
+    DoubleColon,
+    /// Token for the pipe operator `|>`
+    Pipe,
+    /// Token for two dots `..`
+    DoubleDot,
 
     // Keywords
     /// Token for the `type` keyword
@@ -88,12 +150,135 @@ pub enum TokenType<'a> {
     Exposing,
     /// `import` keyword for bringing items into scope
     Import,
+    /// `union` keyword for declaring a sum type
+    Union,
+    /// `record` keyword for declaring a record type
+    Record,
+    /// `struct` keyword for declaring a struct
+    Struct,
+    /// `let` keyword introducing a local binding
+    Let,
+    /// `in` keyword ending the bindings of a `let`
+    In,
+    /// `if` keyword starting a conditional
+    If,
+    /// `then` keyword for the branch taken when the condition holds
+    Then,
+    /// `else` keyword for the branch taken when the condition fails
+    Else,
+    /// `match` keyword for matching a value against patterns
+    Match,
+    /// `case` keyword, an alternative to `match`
+    Case,
+    /// `of` keyword following the scrutinee of a `case`
+    Of,
+    /// `as` keyword for renaming imports and binding patterns
+    As,
+    /// `where` keyword introducing local definitions
+    Where,
+    /// `true` boolean literal
+    True,
+    /// `false` boolean literal
+    False,
+
+    // Virtual tokens, only produced by layout sensitive lexing
+    /// Start of a block indented further than the enclosing one
+    Indent,
+    /// End of an indented block
+    Dedent,
+    /// Start of a new line at the same indentation as the current block
+    Newline,
+
+    /// Stands in for input that could not be tokenized. The details of the
+    /// error are also collected by the lexer
+    Error(LexErrorKind),
 
     /// EOF
     // Not a token but should signal the end of parsing
     EOF,
 }
 
+impl<'a> TokenType<'a> {
+    /// The same token type borrowing from another copy of the source.
+    /// `text` is the source text of the token in that copy
+    pub fn with_source<'b>(&self, text: &'b str) -> TokenType<'b> {
+        use TokenType::*;
+
+        match self {
+            Ident(_) => Ident(text),
+            Comment(_) => Comment(text),
+            // the doc text is the tail of the comment, after the `---` marker
+            DocComment(doc) => DocComment(&text[text.len() - doc.len()..]),
+            IntegerLit(int, suffix) => IntegerLit(*int, *suffix),
+            FloatLit(float, suffix) => FloatLit(*float, *suffix),
+            StringLit(string) => StringLit(string.clone()),
+            CharLit(c) => CharLit(*c),
+            Error(kind) => Error(*kind),
+            Lparen => Lparen,
+            RParen => RParen,
+            Colon => Colon,
+            LBrace => LBrace,
+            RBrace => RBrace,
+            LAngleBracket => LAngleBracket,
+            RAngleBracket => RAngleBracket,
+            LBracket => LBracket,
+            RBracket => RBracket,
+            Equal => Equal,
+            Comma => Comma,
+            Dot => Dot,
+            Plus => Plus,
+            Minus => Minus,
+            Star => Star,
+            Percent => Percent,
+            Vbar => Vbar,
+            Amper => Amper,
+            Exclam => Exclam,
+            Semicolon => Semicolon,
+            At => At,
+            Question => Question,
+            Hash => Hash,
+            Slash => Slash,
+            BackSlash => BackSlash,
+            Caret => Caret,
+            FatArrow => FatArrow,
+            Arrow => Arrow,
+            DoubleEqual => DoubleEqual,
+            NotEqual => NotEqual,
+            LessEqual => LessEqual,
+            GreaterEqual => GreaterEqual,
+            DoubleAmper => DoubleAmper,
+            DoubleVbar => DoubleVbar,
+            DoubleColon => DoubleColon,
+            Pipe => Pipe,
+            DoubleDot => DoubleDot,
+            Type => Type,
+            TypeAlias => TypeAlias,
+            Module => Module,
+            Exposing => Exposing,
+            Import => Import,
+            Union => Union,
+            Record => Record,
+            Struct => Struct,
+            Let => Let,
+            In => In,
+            If => If,
+            Then => Then,
+            Else => Else,
+            Match => Match,
+            Case => Case,
+            Of => Of,
+            As => As,
+            Where => Where,
+            True => True,
+            False => False,
+            Indent => Indent,
+            Dedent => Dedent,
+            Newline => Newline,
+            EOF => EOF,
+        }
+    }
+}
+
 impl<'a> Display for TokenType<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         use TokenType::*;
@@ -102,18 +287,26 @@ impl<'a> Display for TokenType<'a> {
             Ident(ident) => {
                 write!(f, "{ident}")
             }
-            IntegerLit(int) => {
-                write!(f, "{int}")
+            IntegerLit(int, suffix) => {
+                write!(f, "{int}")?;
+                suffix.map_or(Ok(()), |suffix| write!(f, "{suffix}"))
             }
-            FloatLit(num) => {
-                write!(f, "{num}")
+            FloatLit(num, suffix) => {
+                write!(f, "{num}")?;
+                suffix.map_or(Ok(()), |suffix| write!(f, "{suffix}"))
             }
             StringLit(val) => {
                 write!(f, "\"{val}\"")
             }
+            CharLit(c) => {
+                write!(f, "'{c}'")
+            }
             Comment(comment) => {
                 write!(f, "\"{comment}\"")
             }
+            DocComment(doc) => {
+                write!(f, "--- {doc}")
+            }
 
             Lparen => {
                 write!(f, "(")
@@ -136,6 +329,12 @@ impl<'a> Display for TokenType<'a> {
             RAngleBracket => {
                 write!(f, ">")
             }
+            LBracket => {
+                write!(f, "[")
+            }
+            RBracket => {
+                write!(f, "]")
+            }
 
             Equal => {
                 write!(f, "=")
@@ -168,12 +367,37 @@ impl<'a> Display for TokenType<'a> {
             Exclam => {
                 write!(f, "!")
             }
+            Semicolon => {
+                write!(f, ";")
+            }
+            At => {
+                write!(f, "@")
+            }
+            Question => {
+                write!(f, "?")
+            }
+            Hash => {
+                write!(f, "#")
+            }
 
             Slash => {
                 write!(f, "/")
             }
 
             BackSlash => write!(f, "\\"),
+            Caret => write!(f, "^"),
+
+            FatArrow => write!(f, "=>"),
+            Arrow => write!(f, "->"),
+            DoubleEqual => write!(f, "=="),
+            NotEqual => write!(f, "!="),
+            LessEqual => write!(f, "<="),
+            GreaterEqual => write!(f, ">="),
+            DoubleAmper => write!(f, "&&"),
+            DoubleVbar => write!(f, "||"),
+            DoubleColon => write!(f, "::"),
+            Pipe => write!(f, "|>"),
+            DoubleDot => write!(f, ".."),
 
             Type => {
                 write!(f, "type")
@@ -190,6 +414,57 @@ impl<'a> Display for TokenType<'a> {
             Import => {
                 write!(f, "import")
             }
+            Union => {
+                write!(f, "union")
+            }
+            Record => {
+                write!(f, "record")
+            }
+            Struct => {
+                write!(f, "struct")
+            }
+            Let => {
+                write!(f, "let")
+            }
+            In => {
+                write!(f, "in")
+            }
+            If => {
+                write!(f, "if")
+            }
+            Then => {
+                write!(f, "then")
+            }
+            Else => {
+                write!(f, "else")
+            }
+            Match => {
+                write!(f, "match")
+            }
+            Case => {
+                write!(f, "case")
+            }
+            Of => {
+                write!(f, "of")
+            }
+            As => {
+                write!(f, "as")
+            }
+            Where => {
+                write!(f, "where")
+            }
+            True => {
+                write!(f, "true")
+            }
+            False => {
+                write!(f, "false")
+            }
+
+            Indent => write!(f, "<indent>"),
+            Dedent => write!(f, "<dedent>"),
+            Newline => write!(f, "<newline>"),
+
+            Error(kind) => write!(f, "<{kind}>"),
 
             EOF => write!(f, "EOF"),
         }
@@ -0,0 +1,301 @@
+//! Type expressions, found in function signatures, `let` annotations and data
+//! definitions
+//! ```meri
+//! apply: <T>(init: T, f: (T) => T, collection: Vec<T>) => IO(())
+//! ```
+//! A lowercase name is a type variable, as is any name bound by the generic
+//! parameters of the enclosing definition.
+//!
+//! A type is qualified with `.` by the modules leading to it, as in
+//! `Shapes.Rect`, the same way a module is named in `import Shapes.Extra` and a
+//! value from another module is used in `Stdio.print`. The `::` of expressions
+//! and patterns such as `Color::Rgb` instead names a variant of a type, which a
+//! type path never does.
+
+use meri_ast::{Field, Ident, TypeExpr};
+use nom::{
+    combinator::opt,
+    multi::separated_list1,
+    sequence::{delimited, preceded},
+};
+
+use crate::{
+    comma_list, comma_separated,
+    error::{record, Expected},
+    ident, list_end,
+    token::{Token, TokenType},
+    token_type, ParseResult,
+};
+
+pub(crate) fn parse_type<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, TypeExpr<'b>> {
+    match input.first().map(|t| &t.typ) {
+        Some(TokenType::Lparen) => parse_parenthesised_type(input),
+        Some(TokenType::LBrace) => {
+            let (input, fields) = parse_fields(input)?;
+            Ok((input, TypeExpr::Record(fields)))
+        }
+        Some(TokenType::Ident(_)) => parse_named_type(input),
+        _ => {
+            record(input, || {
+                Expected::tokens(&[TokenType::Lparen, TokenType::LBrace])
+            });
+            parse_named_type(input)
+        }
+    }
+}
+
+/// A possibly qualified name followed by generic arguments, given either as
+/// `Option<Int>` or `IO(Unit)`. Empty parentheses `IO ()` apply it to unit
+fn parse_named_type<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, TypeExpr<'b>> {
+    let (input, path) = separated_list1(token_type(TokenType::Dot), ident)(input)?;
+
+    let (input, args) = match input.first().map(|t| &t.typ) {
+        Some(TokenType::LAngleBracket) => delimited(
+            token_type(TokenType::LAngleBracket),
+            separated_list1(token_type(TokenType::Comma), parse_type),
+            list_end(TokenType::RAngleBracket),
+        )(input)?,
+        Some(TokenType::Lparen) => {
+            let (input, args) = preceded(
+                token_type(TokenType::Lparen),
+                comma_list(parse_type, TokenType::RParen),
+            )(input)?;
+            let args = if args.is_empty() {
+                vec![TypeExpr::Unit]
+            } else {
+                args
+            };
+            (input, args)
+        }
+        _ => {
+            record(input, || {
+                Expected::tokens(&[TokenType::LAngleBracket, TokenType::Lparen])
+            });
+            (input, Vec::new())
+        }
+    };
+
+    match &path[..] {
+        [ident] if args.is_empty() && !ident.0.starts_with(|c: char| c.is_uppercase()) => {
+            Ok((input, TypeExpr::Variable(*ident)))
+        }
+        _ => Ok((input, TypeExpr::Named { path, args })),
+    }
+}
+
+/// `()`, `(A)`, a tuple `(A, B)` or a function type `(A, B) => C`
+fn parse_parenthesised_type<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, TypeExpr<'b>> {
+    let (input, mut elements) = preceded(
+        token_type(TokenType::Lparen),
+        comma_list(parse_type, TokenType::RParen),
+    )(input)?;
+
+    if let Ok((input, return_type)) = preceded(token_type(TokenType::FatArrow), parse_type)(input) {
+        return Ok((
+            input,
+            TypeExpr::Function {
+                params: elements,
+                return_type: Box::new(return_type),
+            },
+        ));
+    }
+
+    let typ = match elements.len() {
+        0 => TypeExpr::Unit,
+        1 => elements.remove(0),
+        _ => TypeExpr::Tuple(elements),
+    };
+
+    Ok((input, typ))
+}
+
+/// `{ name: Type, ... }` allowing a trailing comma
+pub(crate) fn parse_fields<'a, 'b>(input: &'a [Token<'b>]) -> ParseResult<'a, 'b, Vec<Field<'b>>> {
+    let field = |input| {
+        let (input, ident) = ident(input)?;
+        let (input, typ) = preceded(token_type(TokenType::Colon), parse_type)(input)?;
+        Ok((input, Field { ident, typ }))
+    };
+
+    delimited(
+        token_type(TokenType::LBrace),
+        comma_separated(field),
+        token_type(TokenType::RBrace),
+    )(input)
+}
+
+/// Generic parameters in angle brackets `<T, U>`. Returns no parameters if
+/// there are none
+pub(crate) fn parse_generic_params<'a, 'b>(
+    input: &'a [Token<'b>],
+) -> ParseResult<'a, 'b, Vec<Ident<'b>>> {
+    let (input, params) = opt(delimited(
+        token_type(TokenType::LAngleBracket),
+        comma_separated(ident),
+        token_type(TokenType::RAngleBracket),
+    ))(input)?;
+
+    Ok((input, params.unwrap_or_default()))
+}
+
+/// Turns the uses of the generic parameters `params` in `typ` into type variables
+pub(crate) fn bind_type_params<'a>(typ: &mut TypeExpr<'a>, params: &[Ident<'a>]) {
+    match typ {
+        TypeExpr::Named { path, args } => match &path[..] {
+            [ident] if args.is_empty() && params.contains(ident) => {
+                *typ = TypeExpr::Variable(*ident);
+            }
+            _ => args
+                .iter_mut()
+                .for_each(|arg| bind_type_params(arg, params)),
+        },
+        TypeExpr::Tuple(elements) => elements
+            .iter_mut()
+            .for_each(|element| bind_type_params(element, params)),
+        TypeExpr::Function {
+            params: fn_params,
+            return_type,
+        } => {
+            fn_params
+                .iter_mut()
+                .for_each(|param| bind_type_params(param, params));
+            bind_type_params(return_type, params);
+        }
+        TypeExpr::Record(fields) => fields
+            .iter_mut()
+            .for_each(|field| bind_type_params(&mut field.typ, params)),
+        TypeExpr::Variable(_) | TypeExpr::Unit => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use meri_ast::{Field, Ident, TypeExpr};
+
+    use crate::{significant_tokens, token::TokenType};
+
+    use super::{bind_type_params, parse_type};
+
+    fn parse(input: &str) -> TypeExpr<'_> {
+        let (tokens, _) = significant_tokens(input);
+        let (rest, typ) = parse_type(&tokens).unwrap();
+        assert_eq!(rest[0].typ, TokenType::EOF, "unparsed input in {input:?}");
+        typ
+    }
+
+    fn named<'a>(name: &'a str, args: Vec<TypeExpr<'a>>) -> TypeExpr<'a> {
+        TypeExpr::Named {
+            path: vec![Ident(name)],
+            args,
+        }
+    }
+
+    #[test]
+    fn named_types() {
+        assert_eq!(parse("Int"), named("Int", vec![]));
+        assert_eq!(
+            parse("Shapes.Rect"),
+            TypeExpr::Named {
+                path: vec![Ident("Shapes"), Ident("Rect")],
+                args: vec![]
+            }
+        );
+        assert_eq!(parse("a"), TypeExpr::Variable(Ident("a")));
+    }
+
+    #[test]
+    fn generic_arguments() {
+        assert_eq!(parse("Vec<Int>"), named("Vec", vec![named("Int", vec![])]));
+        assert_eq!(
+            parse("Either<a, Option<b>>"),
+            named(
+                "Either",
+                vec![
+                    TypeExpr::Variable(Ident("a")),
+                    named("Option", vec![TypeExpr::Variable(Ident("b"))])
+                ]
+            )
+        );
+        assert_eq!(parse("IO(Unit)"), named("IO", vec![named("Unit", vec![])]));
+        assert_eq!(parse("IO ()"), named("IO", vec![TypeExpr::Unit]));
+    }
+
+    #[test]
+    fn tuples_and_unit() {
+        assert_eq!(parse("()"), TypeExpr::Unit);
+        assert_eq!(parse("(Int)"), named("Int", vec![]));
+        assert_eq!(
+            parse("(Int, a)"),
+            TypeExpr::Tuple(vec![named("Int", vec![]), TypeExpr::Variable(Ident("a"))])
+        );
+    }
+
+    #[test]
+    fn function_types() {
+        assert_eq!(
+            parse("(Int) => Int"),
+            TypeExpr::Function {
+                params: vec![named("Int", vec![])],
+                return_type: Box::new(named("Int", vec![]))
+            }
+        );
+        assert_eq!(
+            parse("() => (a, b) => a"),
+            TypeExpr::Function {
+                params: vec![],
+                return_type: Box::new(TypeExpr::Function {
+                    params: vec![
+                        TypeExpr::Variable(Ident("a")),
+                        TypeExpr::Variable(Ident("b"))
+                    ],
+                    return_type: Box::new(TypeExpr::Variable(Ident("a")))
+                })
+            }
+        );
+    }
+
+    #[test]
+    fn record_types() {
+        assert_eq!(
+            parse("{ size: Int, length: a, }"),
+            TypeExpr::Record(vec![
+                Field {
+                    ident: Ident("size"),
+                    typ: named("Int", vec![])
+                },
+                Field {
+                    ident: Ident("length"),
+                    typ: TypeExpr::Variable(Ident("a"))
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn binds_type_params() {
+        let mut typ = parse("(T, Vec<T>) => Option<U>");
+        bind_type_params(&mut typ, &[Ident("T")]);
+        assert_eq!(
+            typ,
+            TypeExpr::Function {
+                params: vec![
+                    TypeExpr::Variable(Ident("T")),
+                    named("Vec", vec![TypeExpr::Variable(Ident("T"))])
+                ],
+                return_type: Box::new(named("Option", vec![named("U", vec![])]))
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_types() {
+        for input in ["Vec<>", "(Int", "{ a }", "Int.", "=> Int"] {
+            let (tokens, _) = significant_tokens(input);
+            let result = parse_type(&tokens);
+            assert!(
+                result.map_or(true, |(rest, _)| rest[0].typ != TokenType::EOF),
+                "{input:?} should not parse"
+            );
+        }
+    }
+}